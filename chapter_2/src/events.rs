@@ -0,0 +1,90 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Address;
+
+// Events emitted by MyLittlePony. Each event is a log whose topic is the event
+// name and whose value is the Borsh-serialized event, so that off-chain clients
+// such as NFT marketplaces can decode them.
+//
+// The events of changes carry the previous and new values and the caller, so that
+// the history of a pony can be reconstructed. There is no event of age change, as
+// the age is derived from the birth timestamp and cannot be changed.
+
+/// The zero address is used as `from` when a pony is minted.
+pub const ZERO_ADDRESS: Address = [0u8; 32];
+
+/// Emitted when a pony is minted or its ownership changes.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Transfer {
+    pub from: Address,
+    pub to: Address,
+    pub pony_id: u64,
+}
+
+/// Emitted when the owner approves an account to transfer one of its ponies.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Approval {
+    pub owner: Address,
+    pub approved: Address,
+    pub pony_id: u64,
+}
+
+/// Emitted when the owner enables or disables an operator to manage all of its ponies.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ApprovalForAll {
+    pub owner: Address,
+    pub operator: Address,
+    pub approved: bool,
+}
+
+/// Emitted when the name of a pony changes. `pony_id` is None for the pony of the contract
+/// itself (Sections 1 to 7), and the id of the pony for the ponies of the registry.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct NameChanged {
+    pub pony_id: Option<u64>,
+    pub caller: Address,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+/// Emitted when the gender or the description of a pony changes, with the values as stored,
+/// i.e. the name of the gender and the custom description (empty for the default description).
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct GenderChanged {
+    pub pony_id: Option<u64>,
+    pub caller: Address,
+    pub old_gender_name: String,
+    pub old_description: String,
+    pub new_gender_name: String,
+    pub new_description: String,
+}
+
+/// Emits `NameChanged` if the name differs.
+pub fn emit_name_change(pony_id: Option<u64>, old_name: &str, new_name: &str) {
+    if old_name != new_name {
+        emit("NameChanged", &NameChanged {
+            pony_id,
+            caller: pchain_sdk::transaction::calling_account(),
+            old_name: old_name.to_string(),
+            new_name: new_name.to_string(),
+        });
+    }
+}
+
+/// Emits `GenderChanged` if the gender or the description differs.
+pub fn emit_gender_change(pony_id: Option<u64>, old: (&str, &str), new: (&str, &str)) {
+    if old != new {
+        emit("GenderChanged", &GenderChanged {
+            pony_id,
+            caller: pchain_sdk::transaction::calling_account(),
+            old_gender_name: old.0.to_string(),
+            old_description: old.1.to_string(),
+            new_gender_name: new.0.to_string(),
+            new_description: new.1.to_string(),
+        });
+    }
+}
+
+pub fn emit<T: BorshSerialize>(topic: &str, event: &T) {
+    pchain_sdk::log(topic.as_bytes(), &event.try_to_vec().unwrap());
+}
@@ -0,0 +1,97 @@
+use std::{fmt, str::FromStr};
+
+// The genders supported by MyLittlePony. A gender may come with a custom
+// description which replaces the default description of the gender.
+//
+// Genders are stored as text, as they were before the genders were restricted
+// to `GenderKind`: the name of the gender, and the custom description or an
+// empty string. New values are written with the canonical name of the gender,
+// while values written by earlier versions of the contract are still read.
+
+/// Maximum number of characters of a custom description.
+pub const MAX_DESCRIPTION_LENGTH: usize = 128;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GenderKind {
+    Female,
+    Male,
+    NonBinary,
+    Unspecified,
+}
+
+impl GenderKind {
+    pub fn default_description(&self) -> &'static str {
+        match self {
+            GenderKind::Female => "she is a mare",
+            GenderKind::Male => "he is a stallion",
+            GenderKind::NonBinary => "they are neither a mare nor a stallion",
+            GenderKind::Unspecified => "they prefer not to say",
+        }
+    }
+
+    /// Reads a stored gender. Names are matched case-insensitively, and a free text name written by
+    /// earlier versions that is not supported is read as `Unspecified`, keeping the free text as the
+    /// description if there is no other description. An empty description stands for no custom description.
+    pub fn from_stored(name: &str, description: &str) -> (GenderKind, Option<String>) {
+        let description = Some(description.to_string()).filter(|description| !description.trim().is_empty());
+        match name.parse() {
+            Ok(kind) => (kind, description),
+            Err(_) => (GenderKind::Unspecified, description.or(Some(name.to_string()))),
+        }
+    }
+}
+
+impl fmt::Display for GenderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GenderKind::Female => "female",
+            GenderKind::Male => "male",
+            GenderKind::NonBinary => "non-binary",
+            GenderKind::Unspecified => "unspecified",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for GenderKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_lowercase().as_str() {
+            "female" | "mare" => Ok(GenderKind::Female),
+            "male" | "stallion" => Ok(GenderKind::Male),
+            "non-binary" | "nonbinary" => Ok(GenderKind::NonBinary),
+            "unspecified" | "" => Ok(GenderKind::Unspecified),
+            _ => Err(format!("Unsupported gender {:?}, expected one of female, male, non-binary or unspecified.", name)),
+        }
+    }
+}
+
+/// Validates the gender name and the optional custom description given to an entrypoint method.
+pub fn parse_gender(name: &str, description: Option<String>) -> Result<(GenderKind, Option<String>), String> {
+    let kind = name.parse()?;
+    if let Some(description) = &description {
+        validate_description(description)?;
+    }
+    Ok((kind, description))
+}
+
+pub fn validate_description(description: &str) -> Result<(), String> {
+    if description.trim().is_empty() {
+        return Err("The custom description must not be blank, omit it to use the default description.".to_string());
+    }
+    if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+        return Err(format!("The custom description is longer than {} characters.", MAX_DESCRIPTION_LENGTH));
+    }
+    Ok(())
+}
+
+/// The name and description to be stored for the gender.
+pub fn to_stored(kind: GenderKind, description: Option<String>) -> (String, String) {
+    (kind.to_string(), description.unwrap_or_default())
+}
+
+/// The custom description if any, or the default description of the gender.
+pub fn describe((kind, description): &(GenderKind, Option<String>)) -> &str {
+    description.as_deref().unwrap_or(kind.default_description())
+}
@@ -0,0 +1,54 @@
+// Templates of the self introduction of a pony, by locale.
+//
+// A template is a text containing the placeholders {name}, {age}, {gender} and
+// {description}, which are replaced by the attributes of the pony. Templates
+// are stored in the contract by the owner. English is built in, and is used
+// for any locale that has no stored template.
+
+/// The built-in English template.
+pub const DEFAULT_TEMPLATE: &str = "Hi, I am {name}. Age of {age}. I am {gender} that means {description}.";
+
+/// Maximum number of characters of a stored template.
+pub const MAX_TEMPLATE_LENGTH: usize = 256;
+
+/// Maximum number of characters of a locale code, e.g. "en", "zh-HK".
+pub const MAX_LOCALE_LENGTH: usize = 16;
+
+pub fn render(template: &str, name: &str, age: u32, gender: &str, description: &str) -> String {
+    template
+        .replace("{name}", name)
+        .replace("{age}", &age.to_string())
+        .replace("{gender}", gender)
+        .replace("{description}", description)
+}
+
+/// Locales to look up in order: the locale itself, then its language alone, e.g. "zh-HK" then "zh".
+pub fn lookup_order(locale: &str) -> Vec<String> {
+    let locale = locale.trim().to_lowercase();
+    let mut locales = vec![locale.clone()];
+    if let Some((language, _)) = locale.split_once('-') {
+        locales.push(language.to_string());
+    }
+    locales
+}
+
+pub fn validate_locale(locale: &str) -> Result<(), String> {
+    let length = locale.chars().count();
+    if length == 0 || length > MAX_LOCALE_LENGTH {
+        return Err(format!("The locale must have between 1 and {} characters.", MAX_LOCALE_LENGTH));
+    }
+    if !locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err("The locale may only contain ASCII letters, digits and '-'.".to_string());
+    }
+    Ok(())
+}
+
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("The template must not be blank.".to_string());
+    }
+    if template.chars().count() > MAX_TEMPLATE_LENGTH {
+        return Err(format!("The template is longer than {} characters.", MAX_TEMPLATE_LENGTH));
+    }
+    Ok(())
+}
@@ -1,63 +1,761 @@
+use borsh::BorshSerialize;
 use pchain_sdk::{
-    contract, contract_methods, call, contract_field
+    contract, contract_methods, call, contract_field, storage, transaction, blockchain, crypto, collections::{FastMap, IterableMap}
 };
 
+mod events;
+mod gender;
+mod introduction;
+mod metadata;
+mod migration;
+mod pony;
+mod state;
+
+use gender::GenderKind;
+use pony::Pony;
+use state::WorldState;
+
 // MyLittlePony is a contract to demonstrate how contract can:
 // - define entrypoint methods
 // - define fields as data in contract storage
+// - restrict entrypoint methods to the owner of the contract
+// - keep many items of the same kind in a registry
+// - treat the items of the registry as transferable non-fungible tokens
+// - derive new items deterministically from existing ones and block data
+// - migrate the world state when the layout of the fields changes
+// - serve text in several locales from templates stored in the contract
+// - describe the items of the registry with JSON metadata documents
+
+type Address = [u8; 32];
+
+/// Key of the field `owner` in world state. It is absent until `init` is called.
+const OWNER_KEY: [u8; 1] = [3];
+/// Keys of the nested fields of `gender` in world state.
+const GENDER_NAME_KEY: [u8; 2] = [2, 0];
+const GENDER_DESCRIPTION_KEY: [u8; 2] = [2, 1];
+/// Key of the field `next_pony_id` in world state. It is absent until `init` is called or the first pony is created.
+const NEXT_PONY_ID_KEY: [u8; 1] = [4];
+/// Maximum number of ponies returned by one call to `list_ponies`, or migrated by one call to `migrate`.
+const MAX_PAGE_SIZE: u32 = 50;
+/// Minimum age of a pony to be a parent in `breed`.
+const MIN_BREEDING_AGE: u32 = 2;
+/// Number of blocks a parent has to wait before it can breed again.
+const BREEDING_COOLDOWN_BLOCKS: u64 = 100;
 
 /// ### Section 1:
 /// The macro `contract` on struct allow loading/storing fields from/into world state.
-/// The key to be store is u8 integer ordered by the order of the fields. E.g. `name` has key [0] while `age` has key [1]
+/// The key to be store is u8 integer ordered by the order of the fields. E.g. `name` has key [0] while `birth_timestamp` has key [1]
 #[contract]
 pub struct MyLittlePony {
     name: String,
-    age: u32,
+    birth_timestamp: u32,
     gender: Gender,
+    owner: Address,
+    next_pony_id: u64,
+    ponies: IterableMap<u64, Pony>,
+    balances: FastMap<Address, u64>,
+    token_approvals: FastMap<u64, Address>,
+    operator_approvals: FastMap<(Address, Address), bool>,
+    breeding_cooldowns: FastMap<u64, u64>,
+    introduction_templates: FastMap<String, String>,
+    image_uris: FastMap<u64, String>,
 }
 
 /// ### Section 2:
 /// The contract field can be used in contract struct so that the key-value pair can be accessed in canonical format.
 /// For example, `name` in Gender has a key [2][0] for contract `MyLittlePony`.
+/// The gender is stored as text so that the state written by earlier versions stays readable, and is
+/// read as `GenderKind` (see module `gender`). An empty description stands for the default description.
 #[contract_field]
 struct Gender {
     name: String,
     description: String
 }
 
+impl Gender {
+    fn new(kind: GenderKind, description: Option<String>) -> Self {
+        let (name, description) = gender::to_stored(kind, description);
+        Gender { name, description }
+    }
+
+    /// The gender and its custom description, if any.
+    fn kind(&self) -> (GenderKind, Option<String>) {
+        GenderKind::from_stored(&self.name, &self.description)
+    }
+}
+
+impl MyLittlePony {
+    /// Logs the reason why the entrypoint method rejected the call.
+    fn reject(method: &str, reason: &str) {
+        pchain_sdk::log(
+            format!("my_little_pony: {}", method).as_bytes(),
+            reason.as_bytes()
+        );
+    }
+
+    /// Returns the owner recorded by `init`, or None if the contract is not initialized yet.
+    fn owner() -> Option<Address> {
+        storage::get(&OWNER_KEY).map(|_| Self::get_owner())
+    }
+
+    /// Checks that the calling account is the owner, and logs the reason of rejection otherwise.
+    fn ensure_owner(method: &str) -> bool {
+        if !Self::ensure_current_layout(method) {
+            return false;
+        }
+        let is_owner = Self::owner() == Some(transaction::calling_account());
+        if !is_owner {
+            Self::reject(method, "Only the owner of the pony can call this method.");
+        }
+        is_owner
+    }
+
+    /// Checks that the world state has the current layout, and logs the reason of rejection otherwise.
+    fn ensure_current_layout(method: &str) -> bool {
+        let is_current = migration::layout_version(&state::Storage) == migration::CURRENT_LAYOUT_VERSION;
+        if !is_current {
+            Self::reject(method, "The storage layout is outdated, call `migrate` first.");
+        }
+        is_current
+    }
+
+    /// Writes only the nested field of `gender` at `key`, and returns the gender before the change for the events.
+    fn replace_gender_field(state: &mut impl WorldState, key: &[u8], value: &String) -> Gender {
+        let old = Gender {
+            name: state::read(state, &GENDER_NAME_KEY).unwrap_or_default(),
+            description: state::read(state, &GENDER_DESCRIPTION_KEY).unwrap_or_default(),
+        };
+        state::write(state, key, value);
+        old
+    }
+
+    /// Whether the account deployed this contract in the transaction with the nonce. The address of a contract
+    /// is the sha256 hash of the deploying account followed by the nonce of the deploy transaction (little-endian).
+    fn is_deployer(account: Address, deployment_nonce: u64) -> bool {
+        let mut pre_image = account.to_vec();
+        pre_image.extend(deployment_nonce.to_le_bytes());
+        crypto::sha256(pre_image) == transaction::current_account()
+    }
+
+    /// Returns the id to be assigned to the next created pony.
+    fn next_pony_id() -> u64 {
+        storage::get(&NEXT_PONY_ID_KEY).map_or(0, |_| Self::get_next_pony_id())
+    }
+
+    /// Checks that the pony exists and is owned by the calling account, and logs the reason of rejection otherwise.
+    fn ensure_pony_owner(method: &str, pony_id: u64) -> Option<Pony> {
+        let rejection = match Self::get_ponies().get(&pony_id) {
+            Some(pony) if pony.owner == transaction::calling_account() => return Some(pony),
+            Some(_) => "Only the owner of the pony can call this method.",
+            None => "No such pony found.",
+        };
+        Self::reject(method, rejection);
+        None
+    }
+
+    /// Whether `operator` may manage all ponies of `owner`.
+    fn is_operator(owner: Address, operator: Address) -> bool {
+        Self::get_operator_approvals().get(&(owner, operator)).unwrap_or(false)
+    }
+
+    /// Whether `spender` may transfer the pony: it is the owner, the approved account of the pony, or an operator of the owner.
+    fn is_approved_or_owner(spender: Address, pony_id: u64, pony: &Pony) -> bool {
+        spender == pony.owner
+            || Self::get_token_approvals().get(&pony_id) == Some(spender)
+            || Self::is_operator(pony.owner, spender)
+    }
+
+    /// Stores the pony under its id in the registry.
+    fn store_pony(pony_id: u64, pony: Pony) {
+        let mut ponies = Self::get_ponies();
+        ponies.insert(&pony_id, pony);
+        Self::set_ponies(ponies);
+    }
+
+    /// Stores a new pony under the next id and emits a `Transfer` event from the zero address.
+    fn mint(pony: Pony) -> u64 {
+        migration::record_layout_version(&mut state::Storage);
+        let pony_id = Self::next_pony_id();
+        let owner = pony.owner;
+        Self::store_pony(pony_id, pony);
+        Self::set_next_pony_id(pony_id + 1);
+        Self::add_balance(owner);
+        events::emit("Transfer", &events::Transfer { from: events::ZERO_ADDRESS, to: owner, pony_id });
+        pony_id
+    }
+
+    /// The introduction template stored for the locale or its language, or the built-in English template.
+    fn introduction_template(locale: &str) -> String {
+        let templates = Self::get_introduction_templates();
+        introduction::lookup_order(locale)
+            .iter()
+            .find_map(|locale| templates.get(locale))
+            .unwrap_or_else(|| introduction::DEFAULT_TEMPLATE.to_string())
+    }
+
+    /// Checks that the pony can be a parent in `breed` for the calling account, or returns the reason of rejection.
+    fn check_parent(caller: Address, pony_id: u64) -> Result<Pony, String> {
+        let pony = Self::get_ponies().get(&pony_id).ok_or(format!("No such pony found: {}.", pony_id))?;
+        if !Self::is_approved_or_owner(caller, pony_id, &pony) {
+            return Err(format!("The caller is not allowed to breed pony {}.", pony_id));
+        }
+        if pony.age(blockchain::timestamp()) < MIN_BREEDING_AGE {
+            return Err(format!("Pony {} is younger than {}.", pony_id, MIN_BREEDING_AGE));
+        }
+        let ready_at = Self::get_breeding_cooldowns().get(&pony_id).unwrap_or(0);
+        if blockchain::block_number() < ready_at {
+            return Err(format!("Pony {} cannot breed before block {}.", pony_id, ready_at));
+        }
+        Ok(pony)
+    }
+
+    fn add_balance(owner: Address) {
+        let mut balances = Self::get_balances();
+        let balance = balances.get(&owner).unwrap_or(0);
+        balances.insert(&owner, balance + 1);
+        Self::set_balances(balances);
+    }
+
+    fn sub_balance(owner: Address) {
+        let mut balances = Self::get_balances();
+        let balance = balances.get(&owner).unwrap_or(0);
+        balances.insert(&owner, balance.saturating_sub(1));
+        Self::set_balances(balances);
+    }
+}
+
 #[contract_methods]
 impl MyLittlePony {
     
     /// ### Section 3: 
     /// Use receiver `&self` to load all data before executing this method. 
     /// All data will be loaded to receiver self from world state.
+    /// The age is computed from the birth timestamp and the timestamp of the current block.
+    /// The introduction uses the template of the locale (see Section 13), or English if there is none.
     #[call]
-    fn self_introduction(&self) -> String {
-        format!("Hi, I am {}. Age of {}. I am {} that means {}.",
-            self.name, self.age, self.gender.name, self.gender.description
+    fn self_introduction(&self, locale: String) -> String {
+        let gender = self.gender.kind();
+        introduction::render(
+            &Self::introduction_template(&locale),
+            &self.name,
+            pony::age_at(self.birth_timestamp, blockchain::timestamp()),
+            &gender.0.to_string(),
+            gender::describe(&gender)
         )
     }
 
     /// ### Section 4:
-    /// This method use contract getter and setter to store the updated data to field `data` to world state. 
-    /// Write cost is small because there is only one key-value pair in world state to be mutated.
+    /// This method use contract getter to load only field `birth_timestamp` from world state.
+    /// Read cost is small because there is only one key-value pair in world state to be loaded.
+    /// The pony grows up with the block timestamp (one year every `SECONDS_PER_PONY_YEAR` seconds),
+    /// so its age cannot be forged by calling this method. It returns the current age of the pony.
     #[call]
-    fn grow_up() {
-        let age = Self::get_age();
-        Self::set_age(age+1)
+    fn grow_up() -> u32 {
+        pony::age_at(Self::get_birth_timestamp(), blockchain::timestamp())
     }
 
     /// ### Section 5:
     /// Use mutable receiver `&mut self` to load data before executing this method, and then store all data after execution.
     /// Be cautious to use mutable receiver as it is expansive to load and storte all key-value pairs in world state
+    /// Only the owner can change the pony. The gender must be one of `GenderKind`, and the description
+    /// is either None for the default description of the gender, or a custom description.
+    /// The events `NameChanged` and `GenderChanged` record the previous and new values that differ.
+    /// Returns whether the pony is changed, so that a contract calling this method can tell a rejection,
+    /// which is logged, from a success.
     #[call]
-    fn change_person(&mut self, name: String, age: u32, gender_name: String, description: String) {
-        pchain_sdk::log(
-            "update_gender".to_string().as_bytes(), 
-            format!("update name:{} description: {}", name, description).as_bytes());
+    fn change_person(&mut self, name: String, gender_name: String, description: Option<String>) -> bool {
+        if !Self::ensure_owner("change_person") {
+            return false;
+        }
+        let (kind, description) = match gender::parse_gender(&gender_name, description) {
+            Ok(gender) => gender,
+            Err(rejection) => {
+                Self::reject("change_person", &rejection);
+                return false;
+            }
+        };
+        let gender = Gender::new(kind, description);
+        events::emit_name_change(None, &self.name, &name);
+        events::emit_gender_change(None,
+            (&self.gender.name, &self.gender.description),
+            (&gender.name, &gender.description)
+        );
         self.name = name;
-        self.age = age;
-        self.gender.name = gender_name;
-        self.gender.description = description;
+        self.gender = gender;
+        true
+    }
+
+    /// ### Section 6:
+    /// Use an associated function without receiver to initialize the fields of the pony with their setters.
+    /// The registry of ponies (Section 8) is left untouched, but its counter `next_pony_id` is written if absent,
+    /// as a receiver (Sections 3 and 5) loads every field and cannot load a field absent from the world state.
+    /// Only the account which deployed the contract can initialize it, so that nobody else can become the owner
+    /// by calling `init` first. It proves it with the nonce of its deploy transaction, see `is_deployer`.
+    /// The calling account becomes the owner of the pony. As `owner` is absent from the world state
+    /// before initialization, a second call to `init` is refused.
+    /// The pony is born at the timestamp of the current block.
+    #[call]
+    fn init(deployment_nonce: u64, name: String, gender_name: String, description: Option<String>) {
+        if !Self::ensure_current_layout("init") {
+            return;
+        }
+        if Self::owner().is_some() {
+            Self::reject("init", "The pony has already been initialized.");
+            return;
+        }
+        if !Self::is_deployer(transaction::calling_account(), deployment_nonce) {
+            Self::reject("init", "Only the deployer of the contract can call this method.");
+            return;
+        }
+        let (kind, description) = match gender::parse_gender(&gender_name, description) {
+            Ok(gender) => gender,
+            Err(rejection) => return Self::reject("init", &rejection),
+        };
+        Self::set_name(name);
+        Self::set_birth_timestamp(blockchain::timestamp());
+        Self::set_gender(Gender::new(kind, description));
+        Self::set_owner(transaction::calling_account());
+        Self::set_next_pony_id(Self::next_pony_id());
+        migration::set_layout_version(&mut state::Storage, migration::CURRENT_LAYOUT_VERSION);
+    }
+
+    /// ### Section 7:
+    /// The owner can hand over the pony to another account. Only field `owner` is written to world state.
+    #[call]
+    fn transfer_ownership(new_owner: Address) {
+        if !Self::ensure_owner("transfer_ownership") {
+            return;
+        }
+        Self::set_owner(new_owner);
+        pchain_sdk::log(
+            "my_little_pony: transfer_ownership".as_bytes(),
+            &new_owner
+        );
+    }
+
+    /// ### Section 8:
+    /// A registry keeps many ponies in the collection `ponies`, each under its own id.
+    /// The methods of the registry have no receiver: a receiver would load the fields of the pony of Sections 1-7
+    /// and, if mutable, write them back when the method returns. Instead, the collections are read with their
+    /// getters and stored with their setters. Entries of the `IterableMap` are only loaded when they are accessed,
+    /// and only the inserted entry is stored.
+    /// The methods which change the registry are refused while the registry is being migrated (Section 11).
+    /// Anyone can create a pony, and the calling account becomes the owner of it.
+    /// The id of the pony is its token id (Section 9), and a `Transfer` event from the zero address is emitted.
+    #[call]
+    fn create_pony(name: String, gender_name: String, description: Option<String>) -> Option<u64> {
+        if !Self::ensure_current_layout("create_pony") {
+            return None;
+        }
+        let (gender_name, gender_description) = match gender::parse_gender(&gender_name, description) {
+            Ok((kind, description)) => gender::to_stored(kind, description),
+            Err(rejection) => {
+                Self::reject("create_pony", &rejection);
+                return None;
+            }
+        };
+        Some(Self::mint(Pony {
+            name,
+            birth_timestamp: blockchain::timestamp(),
+            gender_name,
+            gender_description,
+            owner: transaction::calling_account(),
+        }))
+    }
+
+    /// ### Section 8:
+    /// Only the entry of the requested pony is loaded from world state.
+    #[call]
+    fn get_pony(pony_id: u64) -> Option<Pony> {
+        Self::get_ponies().get(&pony_id)
+    }
+
+    /// ### Section 8:
+    /// Ponies are listed in the order of their ids, starting from `start_id`. At most `limit` ponies
+    /// (and never more than `MAX_PAGE_SIZE`) are returned, so that a page has a bounded gas cost.
+    /// Use the id following the last returned pony as `start_id` of the next page.
+    #[call]
+    fn list_ponies(start_id: u64, limit: u32) -> Vec<(u64, Pony)> {
+        let end_id = Self::next_pony_id().min(start_id.saturating_add(limit.min(MAX_PAGE_SIZE) as u64));
+        let ponies = Self::get_ponies();
+        (start_id..end_id)
+            .filter_map(|pony_id| ponies.get(&pony_id).map(|pony| (pony_id, pony)))
+            .collect()
     }
-}
\ No newline at end of file
+
+    /// ### Section 8:
+    /// Same as `grow_up`, but for a pony in the registry. Returns None if there is no such pony.
+    #[call]
+    fn grow_up_pony(pony_id: u64) -> Option<u32> {
+        Self::get_ponies().get(&pony_id).map(|pony| pony.age(blockchain::timestamp()))
+    }
+
+    /// ### Section 8:
+    /// Same as `change_person`, but for a pony in the registry. Only the owner of that pony can change it.
+    /// The events carry the id of the pony.
+    #[call]
+    fn change_pony(pony_id: u64, name: String, gender_name: String, description: Option<String>) {
+        if !Self::ensure_current_layout("change_pony") {
+            return;
+        }
+        let (gender_name, gender_description) = match gender::parse_gender(&gender_name, description) {
+            Ok((kind, description)) => gender::to_stored(kind, description),
+            Err(rejection) => return Self::reject("change_pony", &rejection),
+        };
+        if let Some(pony) = Self::ensure_pony_owner("change_pony", pony_id) {
+            events::emit_name_change(Some(pony_id), &pony.name, &name);
+            events::emit_gender_change(Some(pony_id),
+                (&pony.gender_name, &pony.gender_description),
+                (&gender_name, &gender_description)
+            );
+            Self::store_pony(pony_id, Pony {
+                name,
+                gender_name,
+                gender_description,
+                ..pony
+            });
+        }
+    }
+
+    /// ### Section 9:
+    /// The ponies of the registry are non-fungible tokens whose token id is the pony id.
+    /// Ownership, balances and approvals only touch the entries of the involved ponies and accounts.
+    #[call]
+    fn owner_of(pony_id: u64) -> Option<Address> {
+        Self::get_ponies().get(&pony_id).map(|pony| pony.owner)
+    }
+
+    /// ### Section 9:
+    /// Number of ponies owned by the account.
+    #[call]
+    fn balance_of(owner: Address) -> u64 {
+        Self::get_balances().get(&owner).unwrap_or(0)
+    }
+
+    /// ### Section 9:
+    /// The owner, or an operator of the owner, can approve one account to transfer the pony.
+    /// Approving the zero address removes the approval.
+    #[call]
+    fn approve(approved: Address, pony_id: u64) {
+        if !Self::ensure_current_layout("approve") {
+            return;
+        }
+        let caller = transaction::calling_account();
+        let owner = match Self::get_ponies().get(&pony_id) {
+            Some(pony) if pony.owner == caller || Self::is_operator(pony.owner, caller) => pony.owner,
+            Some(_) => {
+                Self::reject("approve", "Only the owner of the pony or its operators can approve.");
+                return;
+            },
+            None => {
+                Self::reject("approve", "No such pony found.");
+                return;
+            }
+        };
+        let mut token_approvals = Self::get_token_approvals();
+        if approved == events::ZERO_ADDRESS {
+            token_approvals.remove(&pony_id);
+        } else {
+            token_approvals.insert(&pony_id, approved);
+        }
+        Self::set_token_approvals(token_approvals);
+        events::emit("Approval", &events::Approval { owner, approved, pony_id });
+    }
+
+    /// ### Section 9:
+    /// The account approved to transfer the pony, if any.
+    #[call]
+    fn get_approved(pony_id: u64) -> Option<Address> {
+        Self::get_token_approvals().get(&pony_id)
+    }
+
+    /// ### Section 9:
+    /// Enables or disables an operator to transfer and approve all ponies of the calling account.
+    #[call]
+    fn set_approval_for_all(operator: Address, approved: bool) {
+        if !Self::ensure_current_layout("set_approval_for_all") {
+            return;
+        }
+        let owner = transaction::calling_account();
+        let mut operator_approvals = Self::get_operator_approvals();
+        operator_approvals.insert(&(owner, operator), approved);
+        Self::set_operator_approvals(operator_approvals);
+        events::emit("ApprovalForAll", &events::ApprovalForAll { owner, operator, approved });
+    }
+
+    /// ### Section 9:
+    /// Whether `operator` can manage all ponies of `owner`.
+    #[call]
+    fn is_approved_for_all(owner: Address, operator: Address) -> bool {
+        Self::is_operator(owner, operator)
+    }
+
+    /// ### Section 9:
+    /// Transfers the pony from `from` to `to`. The calling account must be the owner, the approved
+    /// account of the pony, or an operator of the owner. The approval of the pony is cleared.
+    /// The zero address is refused as `to`, as a `Transfer` event from it stands for a newly created pony.
+    #[call]
+    fn transfer(from: Address, to: Address, pony_id: u64) {
+        if !Self::ensure_current_layout("transfer") {
+            return;
+        }
+        if to == events::ZERO_ADDRESS {
+            return Self::reject("transfer", "The pony cannot be transferred to the zero address.");
+        }
+        let caller = transaction::calling_account();
+        let rejection = match Self::get_ponies().get(&pony_id) {
+            None => "No such pony found.",
+            Some(pony) if pony.owner != from => "The pony is not owned by `from`.",
+            Some(pony) if !Self::is_approved_or_owner(caller, pony_id, &pony) => "The caller is not allowed to transfer the pony.",
+            Some(mut pony) => {
+                pony.owner = to;
+                Self::store_pony(pony_id, pony);
+                let mut token_approvals = Self::get_token_approvals();
+                token_approvals.remove(&pony_id);
+                Self::set_token_approvals(token_approvals);
+                Self::sub_balance(from);
+                Self::add_balance(to);
+                events::emit("Transfer", &events::Transfer { from, to, pony_id });
+                return;
+            }
+        };
+        Self::reject("transfer", rejection);
+    }
+
+    /// ### Section 10:
+    /// Two ponies owned by (or approved to) the calling account breed a new pony owned by the calling account.
+    /// Both parents must be at least `MIN_BREEDING_AGE` old, and each of them then waits `BREEDING_COOLDOWN_BLOCKS`
+    /// blocks before breeding again. The attributes of the offspring are derived from the sha256 hash of the parents
+    /// and the block data, so that anyone can verify them but the caller cannot choose them.
+    #[call]
+    fn breed(parent_a_id: u64, parent_b_id: u64) -> Option<u64> {
+        if !Self::ensure_current_layout("breed") {
+            return None;
+        }
+        let caller = transaction::calling_account();
+        let parents = if parent_a_id == parent_b_id {
+            Err("A pony cannot breed with itself.".to_string())
+        } else {
+            Self::check_parent(caller, parent_a_id)
+                .and_then(|parent_a| Self::check_parent(caller, parent_b_id).map(|parent_b| (parent_a, parent_b)))
+        };
+        let (parent_a, parent_b) = match parents {
+            Ok(parents) => parents,
+            Err(rejection) => {
+                Self::reject("breed", &rejection);
+                return None;
+            }
+        };
+
+        let block_number = blockchain::block_number();
+        let mut input = (parent_a_id, parent_b_id, block_number, blockchain::prev_block_hash()).try_to_vec().unwrap();
+        input.extend(parent_a.try_to_vec().unwrap());
+        input.extend(parent_b.try_to_vec().unwrap());
+        let seed = crypto::sha256(input);
+
+        let mut breeding_cooldowns = Self::get_breeding_cooldowns();
+        breeding_cooldowns.insert(&parent_a_id, block_number + BREEDING_COOLDOWN_BLOCKS);
+        breeding_cooldowns.insert(&parent_b_id, block_number + BREEDING_COOLDOWN_BLOCKS);
+        Self::set_breeding_cooldowns(breeding_cooldowns);
+        Some(Self::mint(Pony::offspring(&seed, &parent_a, &parent_b, caller, blockchain::timestamp())))
+    }
+
+    /// ### Section 11:
+    /// The keys of the fields follow their order of declaration, so changing the fields after deployment
+    /// changes the meaning of the stored key-value pairs. `migrate` rewrites the world state written by an
+    /// older version of this contract into the current layout (see module `migration`).
+    /// At most `limit` ponies of the registry (and never more than `MAX_PAGE_SIZE`) are migrated per call, so that
+    /// a call has a bounded gas cost. It returns the layout version reached: call it again until it returns
+    /// `CURRENT_LAYOUT_VERSION`. Until then, the methods which change the registry are refused.
+    /// Once an owner is recorded, only the owner can migrate. Before that, as layout v1 has no owner, only the
+    /// account which deployed the contract can migrate, and it becomes the owner. It proves it with the nonce
+    /// of its deploy transaction, see `is_deployer`.
+    #[call]
+    fn migrate(deployment_nonce: Option<u64>, limit: u32) -> u32 {
+        let version = migration::layout_version(&state::Storage);
+        if version == migration::CURRENT_LAYOUT_VERSION {
+            return version;
+        }
+        let caller = transaction::calling_account();
+        let allowed = match Self::owner() {
+            Some(owner) => owner == caller,
+            None => deployment_nonce.is_some_and(|nonce| Self::is_deployer(caller, nonce)),
+        };
+        if !allowed {
+            Self::reject("migrate", "Only the owner of the pony, or the deployer of the contract if there is no owner, can call this method.");
+            return version;
+        }
+        let mut ponies = Self::get_ponies();
+        let result = migration::migrate(&mut state::Storage, &mut ponies, caller, blockchain::timestamp(), limit.min(MAX_PAGE_SIZE));
+        Self::set_ponies(ponies);
+        match result {
+            Ok(version) => version,
+            Err(rejection) => {
+                Self::reject("migrate", &rejection);
+                migration::layout_version(&state::Storage)
+            }
+        }
+    }
+
+    /// ### Section 12:
+    /// Changing one attribute with `change_person` loads and stores every field of the contract.
+    /// These methods have no receiver and write a single key-value pair instead: `rename` uses the
+    /// setter of field `name` ([0]), while `set_gender_name` and `set_description` write the nested fields
+    /// of `gender` directly at their keys [2][0] and [2][1], leaving the rest of the world state untouched.
+    /// The previous values are read to emit the events `NameChanged` and `GenderChanged`.
+    /// Note that `set_gender` is not available as a method name, as it is the setter generated for field `gender`.
+    #[call]
+    fn rename(name: String) {
+        if !Self::ensure_owner("rename") {
+            return;
+        }
+        events::emit_name_change(None, &Self::get_name(), &name);
+        Self::set_name(name);
+    }
+
+    /// ### Section 12:
+    /// Writes only the key [2][0] of `gender.name`. The gender must be one of `GenderKind`.
+    #[call]
+    fn set_gender_name(gender_name: String) {
+        if !Self::ensure_owner("set_gender_name") {
+            return;
+        }
+        let gender_name = match gender_name.parse::<GenderKind>() {
+            Ok(kind) => kind.to_string(),
+            Err(rejection) => return Self::reject("set_gender_name", &rejection),
+        };
+        let old = Self::replace_gender_field(&mut state::Storage, &GENDER_NAME_KEY, &gender_name);
+        events::emit_gender_change(None, (&old.name, &old.description), (&gender_name, &old.description));
+    }
+
+    /// ### Section 12:
+    /// Writes only the key [2][1] of `gender.description`. None restores the default description of the gender.
+    #[call]
+    fn set_description(description: Option<String>) {
+        if !Self::ensure_owner("set_description") {
+            return;
+        }
+        if let Some(Err(rejection)) = description.as_deref().map(gender::validate_description) {
+            return Self::reject("set_description", &rejection);
+        }
+        let description = description.unwrap_or_default();
+        let old = Self::replace_gender_field(&mut state::Storage, &GENDER_DESCRIPTION_KEY, &description);
+        events::emit_gender_change(None, (&old.name, &old.description), (&old.name, &description));
+    }
+
+    /// ### Section 13:
+    /// Introductions are rendered from templates stored in `introduction_templates` by locale.
+    /// Only the template of the requested locale is loaded, and English is used if no template is stored.
+    #[call]
+    fn introduce_pony(pony_id: u64, locale: String) -> Option<String> {
+        Self::get_ponies().get(&pony_id)
+            .map(|pony| pony.self_introduction(&Self::introduction_template(&locale), blockchain::timestamp()))
+    }
+
+    /// ### Section 13:
+    /// The owner stores the template of a locale, with placeholders {name}, {age}, {gender} and {description}.
+    #[call]
+    fn set_introduction_template(locale: String, template: String) {
+        if !Self::ensure_owner("set_introduction_template") {
+            return;
+        }
+        if let Err(rejection) = introduction::validate_locale(&locale).and(introduction::validate_template(&template)) {
+            return Self::reject("set_introduction_template", &rejection);
+        }
+        let mut templates = Self::get_introduction_templates();
+        templates.insert(&locale.to_lowercase(), template);
+        Self::set_introduction_templates(templates);
+    }
+
+    /// ### Section 13:
+    /// The owner removes the template of a locale, which then falls back to English.
+    #[call]
+    fn remove_introduction_template(locale: String) {
+        if !Self::ensure_owner("remove_introduction_template") {
+            return;
+        }
+        let mut templates = Self::get_introduction_templates();
+        templates.remove(&locale.to_lowercase());
+        Self::set_introduction_templates(templates);
+    }
+
+    /// ### Section 14:
+    /// Wallets display a non-fungible token from a JSON metadata document. The document is built from the
+    /// attributes of the pony, its English introduction as description, and its image URI if one is stored.
+    #[call]
+    fn token_metadata(pony_id: u64) -> Option<String> {
+        let pony = Self::get_ponies().get(&pony_id)?;
+        let now = blockchain::timestamp();
+        let gender = pony.gender();
+        let image = Self::get_image_uris().get(&pony_id);
+        Some(metadata::Metadata {
+            name: &pony.name,
+            description: &pony.self_introduction(introduction::DEFAULT_TEMPLATE, now),
+            image: image.as_deref(),
+            age: pony.age(now),
+            gender: &gender.0.to_string(),
+            gender_description: gender::describe(&gender),
+        }.to_json())
+    }
+
+    /// ### Section 14:
+    /// The owner of the pony stores the URI of its image (https, ipfs or ar), or removes it with None.
+    #[call]
+    fn set_image_uri(pony_id: u64, image_uri: Option<String>) {
+        if !Self::ensure_current_layout("set_image_uri") {
+            return;
+        }
+        if let Some(Err(rejection)) = image_uri.as_deref().map(metadata::validate_image_uri) {
+            return Self::reject("set_image_uri", &rejection);
+        }
+        if Self::ensure_pony_owner("set_image_uri", pony_id).is_none() {
+            return;
+        }
+        let mut image_uris = Self::get_image_uris();
+        match image_uri {
+            Some(image_uri) => { image_uris.insert(&pony_id, image_uri); },
+            None => { image_uris.remove(&pony_id); },
+        }
+        Self::set_image_uris(image_uris);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use state::memory::MemoryState;
+
+    /// The fields of the pony written by `init`.
+    fn initialized_pony() -> MemoryState {
+        let mut state = MemoryState::default();
+        state::write(&mut state, &[0], &"Sparkle".to_string());
+        state::write(&mut state, &[1], &0u32);
+        state::write(&mut state, &GENDER_NAME_KEY, &"Female".to_string());
+        state::write(&mut state, &GENDER_DESCRIPTION_KEY, &"Loves apples.".to_string());
+        state::write(&mut state, &OWNER_KEY, &[1u8; 32]);
+        state.reset_counters();
+        state
+    }
+
+    /// Number of bytes of the key and the Borsh-encoded value of a key-value pair.
+    fn pair_size(key: &[u8], value: &str) -> usize {
+        key.len() + value.to_string().try_to_vec().unwrap().len()
+    }
+
+    #[test]
+    fn replacing_a_gender_field_accesses_only_the_gender() {
+        let mut state = initialized_pony();
+        let old = MyLittlePony::replace_gender_field(&mut state, &GENDER_DESCRIPTION_KEY, &"Loves pears.".to_string());
+        assert_eq!((old.name.as_str(), old.description.as_str()), ("Female", "Loves apples."));
+        // Both nested fields of `gender` are read for the events, and only the changed one is written.
+        assert_eq!(
+            state.bytes_accessed(),
+            pair_size(&GENDER_NAME_KEY, "Female")
+                + pair_size(&GENDER_DESCRIPTION_KEY, "Loves apples.")
+                + pair_size(&GENDER_DESCRIPTION_KEY, "Loves pears.")
+        );
+        assert_eq!(state::read::<String>(&state, &GENDER_DESCRIPTION_KEY), Some("Loves pears.".to_string()));
+        assert_eq!(state::read::<String>(&state, &GENDER_NAME_KEY), Some("Female".to_string()));
+        assert_eq!(state::read::<String>(&state, &[0]), Some("Sparkle".to_string()));
+    }
+}
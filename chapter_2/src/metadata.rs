@@ -0,0 +1,72 @@
+// Metadata documents of the ponies, in the JSON format commonly used by wallets
+// and marketplaces to display non-fungible tokens:
+//
+// {"name":"...","description":"...","image":"...","attributes":[{"trait_type":"Age","value":3}, ...]}
+//
+// The document is built from the attributes of the pony, so that clients do not
+// need to know how the pony is stored. `image` is only present if an image URI
+// is stored for the pony.
+
+/// Maximum number of characters of an image URI.
+pub const MAX_IMAGE_URI_LENGTH: usize = 256;
+
+/// URI schemes accepted for images.
+const IMAGE_URI_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
+
+pub struct Metadata<'a> {
+    pub name: &'a str,
+    pub description: &'a str,
+    pub image: Option<&'a str>,
+    pub age: u32,
+    pub gender: &'a str,
+    pub gender_description: &'a str,
+}
+
+impl Metadata<'_> {
+    pub fn to_json(&self) -> String {
+        let image = self.image
+            .map(|image| format!(",\"image\":{}", json_string(image)))
+            .unwrap_or_default();
+        format!(
+            "{{\"name\":{},\"description\":{}{},\"attributes\":[{{\"trait_type\":\"Age\",\"value\":{}}},{{\"trait_type\":\"Gender\",\"value\":{}}},{{\"trait_type\":\"Gender Description\",\"value\":{}}}]}}",
+            json_string(self.name),
+            json_string(self.description),
+            image,
+            self.age,
+            json_string(self.gender),
+            json_string(self.gender_description),
+        )
+    }
+}
+
+/// Encodes the text as a JSON string, including the quotes.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+pub fn validate_image_uri(uri: &str) -> Result<(), String> {
+    if uri.chars().count() > MAX_IMAGE_URI_LENGTH {
+        return Err(format!("The image URI is longer than {} characters.", MAX_IMAGE_URI_LENGTH));
+    }
+    if !IMAGE_URI_SCHEMES.iter().any(|scheme| uri.starts_with(scheme)) {
+        return Err(format!("The image URI must start with one of {}.", IMAGE_URI_SCHEMES.join(", ")));
+    }
+    if uri.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err("The image URI must not contain whitespaces.".to_string());
+    }
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use pchain_sdk::collections::IterableMap;
+
+use crate::{
+    Address, OWNER_KEY, NEXT_PONY_ID_KEY,
+    pony::{Pony, SECONDS_PER_PONY_YEAR},
+    state::{self, WorldState},
+};
+
+// The fields of a `#[contract]` struct are stored at keys given by their order
+// of declaration, e.g. [0] name, [1] age, [2][0] gender name. Adding, removing
+// or reordering fields after deployment changes the meaning of these keys, so
+// the contract records the version of its storage layout and provides the
+// entrypoint `migrate` to rewrite the world state from an older layout.
+//
+// Layout history:
+// - v1: [0] name, [1] age, [2][0] gender name, [2][1] gender description
+// - v2: v1 + [3] owner, and the registry of ponies from [4] onwards
+// - v3: [1] and the ponies of the registry store the birth timestamp instead of the age
+
+/// The layout written by this version of the contract.
+pub const CURRENT_LAYOUT_VERSION: u32 = 3;
+
+/// Key of the layout version. It is not a field of the contract struct, so that
+/// it keeps the same key however the fields are changed in later layouts.
+const LAYOUT_VERSION_KEY: [u8; 1] = [255];
+/// Key of the id of the next pony of the registry to be migrated to layout v3.
+/// It is absent until the migration to layout v3 starts.
+const MIGRATION_CURSOR_KEY: [u8; 1] = [254];
+
+/// Keys of the pony in the layout v1.
+const V1_NAME_KEY: [u8; 1] = [0];
+const V1_AGE_KEY: [u8; 1] = [1];
+const V1_GENDER_NAME_KEY: [u8; 2] = [2, 0];
+const V1_GENDER_DESCRIPTION_KEY: [u8; 2] = [2, 1];
+
+/// The ponies of the registry, by id.
+pub trait Registry {
+    fn get(&self, pony_id: u64) -> Option<Pony>;
+    fn insert(&mut self, pony_id: u64, pony: Pony);
+}
+
+impl Registry for IterableMap<u64, Pony> {
+    fn get(&self, pony_id: u64) -> Option<Pony> {
+        IterableMap::get(self, &pony_id)
+    }
+
+    fn insert(&mut self, pony_id: u64, pony: Pony) {
+        IterableMap::insert(self, &pony_id, pony);
+    }
+}
+
+/// Returns the layout of the world state. States written before the layout version
+/// was recorded are recognised by the keys they contain, and an empty state is
+/// considered to have the current layout.
+pub fn layout_version(state: &impl WorldState) -> u32 {
+    if let Some(version) = state::read::<u32>(state, &LAYOUT_VERSION_KEY) {
+        return version;
+    }
+    if state.get(&V1_NAME_KEY).is_some() {
+        if state.get(&OWNER_KEY).is_some() { 2 } else { 1 }
+    } else if state.get(&NEXT_PONY_ID_KEY).is_some() {
+        // a registry without the pony of `init`, written by layout v2
+        2
+    } else {
+        CURRENT_LAYOUT_VERSION
+    }
+}
+
+pub fn set_layout_version(state: &mut impl WorldState, version: u32) {
+    state::write(state, &LAYOUT_VERSION_KEY, &version);
+}
+
+/// Records the layout recognised from the keys if no layout is recorded yet. It is called before the
+/// first write to an empty state, which would otherwise no longer be recognised as having the current layout.
+pub fn record_layout_version(state: &mut impl WorldState) {
+    if state.get(&LAYOUT_VERSION_KEY).is_none() {
+        set_layout_version(state, layout_version(state));
+    }
+}
+
+/// Migrates the world state one layout at a time up to the current layout, as of block timestamp `now`.
+/// `owner` becomes the owner of a pony of layout v1, which has none. The caller of this function
+/// must have checked that `owner` is entitled to it. At most `limit` ponies of the registry are
+/// migrated per call, so the layout reached may be older than the current one. Returns the reason
+/// of failure if the old state cannot be read.
+pub fn migrate(state: &mut impl WorldState, ponies: &mut impl Registry, owner: Address, now: u32, limit: u32) -> Result<u32, String> {
+    let mut version = layout_version(state);
+    while version < CURRENT_LAYOUT_VERSION {
+        match version {
+            1 => migrate_v1_to_v2(state, owner)?,
+            2 => if !migrate_v2_to_v3(state, ponies, now, limit) {
+                return Ok(version);
+            },
+            _ => return Err(format!("No migration from layout v{}.", version)),
+        }
+        version += 1;
+        set_layout_version(state, version);
+    }
+    Ok(version)
+}
+
+/// Layout v1 has no owner nor registry. The pony keeps its keys, `owner` is recorded at the key of field `owner`,
+/// and the counter `next_pony_id` of the empty registry is written, as `init` does.
+fn migrate_v1_to_v2(state: &mut impl WorldState, owner: Address) -> Result<(), String> {
+    state::read::<String>(state, &V1_NAME_KEY).ok_or("Cannot read name of layout v1.")?;
+    state::read::<u32>(state, &V1_AGE_KEY).ok_or("Cannot read age of layout v1.")?;
+    state::read::<String>(state, &V1_GENDER_NAME_KEY).ok_or("Cannot read gender name of layout v1.")?;
+    state::read::<String>(state, &V1_GENDER_DESCRIPTION_KEY).ok_or("Cannot read gender description of layout v1.")?;
+    state::write(state, &OWNER_KEY, &owner);
+    if state.get(&NEXT_PONY_ID_KEY).is_none() {
+        state::write(state, &NEXT_PONY_ID_KEY, &0u64);
+    }
+    Ok(())
+}
+
+/// Layout v3 replaces the age by the birth timestamp at the same keys. The pony is considered
+/// born `age` pony years before the current block. The `Pony` of the registry keeps the same
+/// Borsh layout, so an entry written in layout v2 deserializes with the age in `birth_timestamp`.
+/// The ponies of the registry are migrated in order of their ids, at most `limit` of them per call, and
+/// the id to continue from is recorded. Returns whether all the ponies are migrated.
+fn migrate_v2_to_v3(state: &mut impl WorldState, ponies: &mut impl Registry, now: u32, limit: u32) -> bool {
+    let birth_timestamp = |age: u32| now.saturating_sub(age.saturating_mul(SECONDS_PER_PONY_YEAR));
+
+    let start_id = match state::read::<u64>(state, &MIGRATION_CURSOR_KEY) {
+        Some(start_id) => start_id,
+        None => {
+            if let Some(age) = state::read::<u32>(state, &V1_AGE_KEY) {
+                state::write(state, &V1_AGE_KEY, &birth_timestamp(age));
+            }
+            0
+        }
+    };
+
+    let next_pony_id = state::read::<u64>(state, &NEXT_PONY_ID_KEY).unwrap_or(0);
+    let end_id = next_pony_id.min(start_id.saturating_add(limit as u64));
+    for pony_id in start_id..end_id {
+        if let Some(pony) = ponies.get(pony_id) {
+            let age = pony.birth_timestamp;
+            ponies.insert(pony_id, Pony { birth_timestamp: birth_timestamp(age), ..pony });
+        }
+    }
+    state::write(state, &MIGRATION_CURSOR_KEY, &end_id);
+    end_id == next_pony_id
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::state::memory::MemoryState;
+
+    const DEPLOYER: Address = [1u8; 32];
+    const NOW: u32 = 1_000 * SECONDS_PER_PONY_YEAR;
+    const LIMIT: u32 = 50;
+
+    impl Registry for BTreeMap<u64, Pony> {
+        fn get(&self, pony_id: u64) -> Option<Pony> {
+            BTreeMap::get(self, &pony_id).cloned()
+        }
+
+        fn insert(&mut self, pony_id: u64, pony: Pony) {
+            BTreeMap::insert(self, pony_id, pony);
+        }
+    }
+
+    /// The world state written by `init` of layout v1.
+    fn deploy_v1(age: u32) -> MemoryState {
+        let mut state = MemoryState::default();
+        state::write(&mut state, &V1_NAME_KEY, &"Sparkle".to_string());
+        state::write(&mut state, &V1_AGE_KEY, &age);
+        state::write(&mut state, &V1_GENDER_NAME_KEY, &"Female".to_string());
+        state::write(&mut state, &V1_GENDER_DESCRIPTION_KEY, &String::new());
+        state
+    }
+
+    fn pony(birth_timestamp: u32) -> Pony {
+        Pony {
+            name: "Applejack".to_string(),
+            birth_timestamp,
+            gender_name: "Female".to_string(),
+            gender_description: String::new(),
+            owner: DEPLOYER,
+        }
+    }
+
+    #[test]
+    fn empty_state_has_current_layout() {
+        assert_eq!(layout_version(&MemoryState::default()), CURRENT_LAYOUT_VERSION);
+    }
+
+    #[test]
+    fn layout_version_is_inferred_from_keys() {
+        let mut state = deploy_v1(3);
+        assert_eq!(layout_version(&state), 1);
+        state::write(&mut state, &OWNER_KEY, &DEPLOYER);
+        assert_eq!(layout_version(&state), 2);
+    }
+
+    #[test]
+    fn registry_without_pony_is_inferred_as_v2() {
+        let mut state = MemoryState::default();
+        state::write(&mut state, &NEXT_PONY_ID_KEY, &1u64);
+        assert_eq!(layout_version(&state), 2);
+        record_layout_version(&mut state);
+        assert_eq!(layout_version(&state), 2);
+    }
+
+    #[test]
+    fn layout_version_is_recorded_before_first_write() {
+        let mut state = MemoryState::default();
+        record_layout_version(&mut state);
+        state::write(&mut state, &NEXT_PONY_ID_KEY, &1u64);
+        assert_eq!(layout_version(&state), CURRENT_LAYOUT_VERSION);
+    }
+
+    #[test]
+    fn recorded_layout_version_takes_precedence() {
+        let mut state = deploy_v1(3);
+        set_layout_version(&mut state, 2);
+        assert_eq!(layout_version(&state), 2);
+    }
+
+    #[test]
+    fn migrates_v1_to_current_layout() {
+        let mut state = deploy_v1(3);
+        assert_eq!(migrate(&mut state, &mut BTreeMap::new(), DEPLOYER, NOW, LIMIT), Ok(CURRENT_LAYOUT_VERSION));
+        assert_eq!(layout_version(&state), CURRENT_LAYOUT_VERSION);
+        assert_eq!(state::read::<Address>(&state, &OWNER_KEY), Some(DEPLOYER));
+        assert_eq!(state::read::<u64>(&state, &NEXT_PONY_ID_KEY), Some(0));
+        assert_eq!(state::read::<u32>(&state, &V1_AGE_KEY), Some(NOW - 3 * SECONDS_PER_PONY_YEAR));
+        assert_eq!(state::read::<String>(&state, &V1_NAME_KEY), Some("Sparkle".to_string()));
+    }
+
+    #[test]
+    fn unreadable_v1_state_is_left_untouched() {
+        let mut state = deploy_v1(3);
+        state.set(&V1_GENDER_DESCRIPTION_KEY, &[0xff]);
+        assert!(migrate(&mut state, &mut BTreeMap::new(), DEPLOYER, NOW, LIMIT).is_err());
+        assert_eq!(layout_version(&state), 1);
+        assert_eq!(state.get(&OWNER_KEY), None);
+        assert_eq!(state.get(&NEXT_PONY_ID_KEY), None);
+    }
+
+    #[test]
+    fn migrates_ages_of_registry_from_v2() {
+        let mut state = deploy_v1(3);
+        state::write(&mut state, &OWNER_KEY, &DEPLOYER);
+        state::write(&mut state, &NEXT_PONY_ID_KEY, &2u64);
+        let mut ponies = BTreeMap::from([(0, pony(5)), (1, pony(0))]);
+
+        assert_eq!(migrate(&mut state, &mut ponies, [2u8; 32], NOW, LIMIT), Ok(CURRENT_LAYOUT_VERSION));
+        assert_eq!(ponies[&0].age(NOW), 5);
+        assert_eq!(ponies[&1].age(NOW), 0);
+        assert_eq!(state::read::<Address>(&state, &OWNER_KEY), Some(DEPLOYER));
+    }
+
+    #[test]
+    fn migrates_registry_in_pages() {
+        let mut state = deploy_v1(3);
+        state::write(&mut state, &OWNER_KEY, &DEPLOYER);
+        state::write(&mut state, &NEXT_PONY_ID_KEY, &3u64);
+        let mut ponies = BTreeMap::from([(0, pony(1)), (1, pony(2)), (2, pony(4))]);
+
+        assert_eq!(migrate(&mut state, &mut ponies, DEPLOYER, NOW, 2), Ok(2));
+        assert_eq!((ponies[&0].age(NOW), ponies[&1].age(NOW)), (1, 2));
+        assert_eq!(ponies[&2].birth_timestamp, 4);
+        assert_eq!(state::read::<u32>(&state, &V1_AGE_KEY), Some(NOW - 3 * SECONDS_PER_PONY_YEAR));
+
+        assert_eq!(migrate(&mut state, &mut ponies, DEPLOYER, NOW + 1, 2), Ok(CURRENT_LAYOUT_VERSION));
+        assert_eq!((ponies[&0].age(NOW + 1), ponies[&1].age(NOW + 1), ponies[&2].age(NOW + 1)), (1, 2, 4));
+        assert_eq!(state::read::<u32>(&state, &V1_AGE_KEY), Some(NOW - 3 * SECONDS_PER_PONY_YEAR));
+    }
+
+    #[test]
+    fn current_layout_is_not_migrated_again() {
+        let mut state = deploy_v1(3);
+        set_layout_version(&mut state, CURRENT_LAYOUT_VERSION);
+        assert_eq!(migrate(&mut state, &mut BTreeMap::new(), DEPLOYER, NOW, LIMIT), Ok(CURRENT_LAYOUT_VERSION));
+        assert_eq!(state::read::<u32>(&state, &V1_AGE_KEY), Some(3));
+    }
+}
@@ -0,0 +1,61 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::{Address, gender::{self, GenderKind}, introduction};
+
+/// Number of seconds of block time for a pony to grow one year older.
+pub const SECONDS_PER_PONY_YEAR: u32 = 86_400;
+
+/// Age of a pony born at `birth_timestamp`, as of block timestamp `now`.
+pub fn age_at(birth_timestamp: u32, now: u32) -> u32 {
+    now.saturating_sub(birth_timestamp) / SECONDS_PER_PONY_YEAR
+}
+
+// A pony stored in the registry of MyLittlePony.
+//
+// Unlike the fields of the contract struct, a `Pony` is stored as a single
+// Borsh-serialized value under its id in the `ponies` collection, so that any
+// number of ponies can be kept by the same contract.
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct Pony {
+    pub name: String,
+    pub birth_timestamp: u32,
+    pub gender_name: String,
+    pub gender_description: String,
+    pub owner: Address,
+}
+
+impl Pony {
+    /// Derives the offspring of two parents from a seed. The same parents and seed always give the same pony:
+    /// - the name joins the first half of one parent's name with the second half of the other's
+    /// - the gender is inherited from one of the parents
+    /// - the pony is born at `birth_timestamp`
+    pub fn offspring(seed: &[u8], parent_a: &Pony, parent_b: &Pony, owner: Address, birth_timestamp: u32) -> Pony {
+        let (first, second) = if seed[0].is_multiple_of(2) { (parent_a, parent_b) } else { (parent_b, parent_a) };
+        let gender_parent = if seed[1].is_multiple_of(2) { parent_a } else { parent_b };
+
+        let first_half: String = first.name.chars().take(first.name.chars().count().div_ceil(2)).collect();
+        let second_half: String = second.name.chars().skip(second.name.chars().count() / 2).collect();
+
+        Pony {
+            name: first_half + &second_half,
+            birth_timestamp,
+            gender_name: gender_parent.gender_name.clone(),
+            gender_description: gender_parent.gender_description.clone(),
+            owner,
+        }
+    }
+
+    pub fn age(&self, now: u32) -> u32 {
+        age_at(self.birth_timestamp, now)
+    }
+
+    /// The gender of the pony and its custom description, if any.
+    pub fn gender(&self) -> (GenderKind, Option<String>) {
+        GenderKind::from_stored(&self.gender_name, &self.gender_description)
+    }
+
+    pub fn self_introduction(&self, template: &str, now: u32) -> String {
+        let gender = self.gender();
+        introduction::render(template, &self.name, self.age(now), &gender.0.to_string(), gender::describe(&gender))
+    }
+}
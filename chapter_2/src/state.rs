@@ -0,0 +1,74 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use pchain_sdk::storage;
+
+// Raw key-value pairs of the world state. The fields of the contract struct are
+// read and written with their getters and setters, while the code working with
+// keys directly (e.g. the migration of old layouts) goes through `WorldState`, so
+// that it also runs against the in-memory state of the tests.
+
+pub trait WorldState {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+    fn set(&mut self, key: &[u8], value: &[u8]);
+}
+
+/// The world state of the contract, accessed with the SDK.
+pub struct Storage;
+
+impl WorldState for Storage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        storage::get(key)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        storage::set(key, value)
+    }
+}
+
+pub fn read<T: BorshDeserialize>(state: &impl WorldState, key: &[u8]) -> Option<T> {
+    state.get(key).and_then(|bytes| T::deserialize(&mut bytes.as_ref()).ok())
+}
+
+pub fn write<T: BorshSerialize>(state: &mut impl WorldState, key: &[u8], value: &T) {
+    state.set(key, &value.try_to_vec().unwrap());
+}
+
+#[cfg(test)]
+pub mod memory {
+    use std::{cell::Cell, collections::HashMap};
+
+    use super::WorldState;
+
+    /// World state kept in memory. It counts the bytes of keys and values read and written,
+    /// which is what the gas of world state access is charged for.
+    #[derive(Default)]
+    pub struct MemoryState {
+        pairs: HashMap<Vec<u8>, Vec<u8>>,
+        bytes_read: Cell<usize>,
+        bytes_written: usize,
+    }
+
+    impl MemoryState {
+        /// Number of bytes read and written since the state was created or the counters were reset.
+        pub fn bytes_accessed(&self) -> usize {
+            self.bytes_read.get() + self.bytes_written
+        }
+
+        pub fn reset_counters(&mut self) {
+            self.bytes_read.set(0);
+            self.bytes_written = 0;
+        }
+    }
+
+    impl WorldState for MemoryState {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            let value = self.pairs.get(key).cloned();
+            self.bytes_read.set(self.bytes_read.get() + key.len() + value.as_ref().map_or(0, Vec::len));
+            value
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.bytes_written += key.len() + value.len();
+            self.pairs.insert(key.to_vec(), value.to_vec());
+        }
+    }
+}
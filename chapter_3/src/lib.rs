@@ -7,6 +7,7 @@ use pchain_sdk::{
 };
 
 mod bank_account;
+mod validation;
 
 use bank_account::BankAccount;
 /// ### Section 1:
@@ -23,13 +24,25 @@ struct MyBank {
 impl MyBank {
 
     /// entrypoint method "open_account"
+    /// Returns the id of the opened account, or the reason why the account details are invalid,
+    /// so that a contract calling this method can tell a rejection from a success.
     #[call]
     fn open_account(
         first_name: String,
         last_name: String,
         account_id: String,
         initial_deposit: u64,
-    ) {
+    ) -> Result<String, String> {
+        // reject malformed inputs before anything is written to the world state
+        if let Err(error) = validation::validate_new_account(&first_name, &last_name, &account_id) {
+            let reason = format!("Invalid account details: {}", error);
+            pchain_sdk::log(
+                "bank_account: Open".as_bytes(),
+                reason.as_bytes()
+            );
+            return Err(reason);
+        }
+
         let parsed_account_id= 
         if account_id != "" {
             account_id.to_owned().as_bytes().to_vec()
@@ -64,6 +77,8 @@ impl MyBank {
             &opened_bank_account.last_name,
             &opened_bank_account.account_id).as_bytes()
         );
+
+        Ok(opened_bank_account.account_id)
     }
 
     /// entrypoint method "query_account_balance"
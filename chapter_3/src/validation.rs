@@ -0,0 +1,155 @@
+use std::fmt;
+
+// Validation rules applied to the inputs of `open_account` before anything
+// is written to the world state. Keeping the names and account ids bounded
+// keeps both the stored `BankAccount` and the emitted logs small.
+
+/// Maximum number of characters allowed in a first or last name.
+pub const MAX_NAME_LENGTH: usize = 64;
+/// Minimum number of characters of a user supplied account id.
+pub const MIN_ACCOUNT_ID_LENGTH: usize = 6;
+/// Maximum number of characters of a user supplied account id.
+pub const MAX_ACCOUNT_ID_LENGTH: usize = 32;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    EmptyName { field: &'static str },
+    NameTooLong { field: &'static str, length: usize },
+    InvalidNameCharacter { field: &'static str, character: char },
+    AccountIdLength { length: usize },
+    InvalidAccountIdCharacter { character: char },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyName { field } =>
+                write!(f, "{} must not be empty", field),
+            ValidationError::NameTooLong { field, length } =>
+                write!(f, "{} has {} characters, the maximum is {}", field, length, MAX_NAME_LENGTH),
+            ValidationError::InvalidNameCharacter { field, character } =>
+                write!(f, "{} contains invalid character {:?}, only letters, spaces, '-' and '\\'' are allowed", field, character),
+            ValidationError::AccountIdLength { length } =>
+                write!(f, "account_id has {} characters, it must have between {} and {}", length, MIN_ACCOUNT_ID_LENGTH, MAX_ACCOUNT_ID_LENGTH),
+            ValidationError::InvalidAccountIdCharacter { character } =>
+                write!(f, "account_id contains invalid character {:?}, only ASCII letters, digits, '-' and '_' are allowed", character),
+        }
+    }
+}
+
+/// A name must not be blank, must fit in `MAX_NAME_LENGTH` characters and may only
+/// contain letters, spaces, hyphens and apostrophes (e.g. "Mary-Jane", "O'Neil").
+pub fn validate_name(field: &'static str, name: &str) -> Result<(), ValidationError> {
+    if name.trim().is_empty() {
+        return Err(ValidationError::EmptyName { field });
+    }
+    let length = name.chars().count();
+    if length > MAX_NAME_LENGTH {
+        return Err(ValidationError::NameTooLong { field, length });
+    }
+    match name.chars().find(|c| !(c.is_alphabetic() || *c == ' ' || *c == '-' || *c == '\'')) {
+        Some(character) => Err(ValidationError::InvalidNameCharacter { field, character }),
+        None => Ok(()),
+    }
+}
+
+/// An empty account id is accepted, as `open_account` then derives one from the names.
+/// Otherwise it must be between `MIN_ACCOUNT_ID_LENGTH` and `MAX_ACCOUNT_ID_LENGTH`
+/// ASCII letters, digits, '-' or '_'.
+pub fn validate_account_id(account_id: &str) -> Result<(), ValidationError> {
+    if account_id.is_empty() {
+        return Ok(());
+    }
+    let length = account_id.chars().count();
+    if !(MIN_ACCOUNT_ID_LENGTH..=MAX_ACCOUNT_ID_LENGTH).contains(&length) {
+        return Err(ValidationError::AccountIdLength { length });
+    }
+    match account_id.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_')) {
+        Some(character) => Err(ValidationError::InvalidAccountIdCharacter { character }),
+        None => Ok(()),
+    }
+}
+
+/// Validates all the user supplied inputs of `open_account`, reporting the first violation.
+pub fn validate_new_account(first_name: &str, last_name: &str, account_id: &str) -> Result<(), ValidationError> {
+    validate_name("first_name", first_name)?;
+    validate_name("last_name", last_name)?;
+    validate_account_id(account_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_valid_names() {
+        assert_eq!(validate_name("first_name", "Mary-Jane"), Ok(()));
+        assert_eq!(validate_name("last_name", "O'Neil"), Ok(()));
+        assert_eq!(validate_name("last_name", "van der Berg"), Ok(()));
+        assert_eq!(validate_name("first_name", "Zoë"), Ok(()));
+        assert_eq!(validate_name("first_name", &"a".repeat(MAX_NAME_LENGTH)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_blank_names() {
+        assert_eq!(validate_name("first_name", ""), Err(ValidationError::EmptyName { field: "first_name" }));
+        assert_eq!(validate_name("last_name", "   "), Err(ValidationError::EmptyName { field: "last_name" }));
+    }
+
+    #[test]
+    fn rejects_long_names() {
+        let name = "é".repeat(MAX_NAME_LENGTH + 1);
+        assert_eq!(
+            validate_name("first_name", &name),
+            Err(ValidationError::NameTooLong { field: "first_name", length: MAX_NAME_LENGTH + 1 })
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_name_characters() {
+        assert_eq!(
+            validate_name("last_name", "Smith2"),
+            Err(ValidationError::InvalidNameCharacter { field: "last_name", character: '2' })
+        );
+        assert_eq!(
+            validate_name("first_name", "Ann\n"),
+            Err(ValidationError::InvalidNameCharacter { field: "first_name", character: '\n' })
+        );
+    }
+
+    #[test]
+    fn accepts_valid_account_ids() {
+        assert_eq!(validate_account_id(""), Ok(()));
+        assert_eq!(validate_account_id("acc_01-x"), Ok(()));
+        assert_eq!(validate_account_id(&"a".repeat(MIN_ACCOUNT_ID_LENGTH)), Ok(()));
+        assert_eq!(validate_account_id(&"a".repeat(MAX_ACCOUNT_ID_LENGTH)), Ok(()));
+    }
+
+    #[test]
+    fn rejects_account_ids_of_wrong_length() {
+        let short = "a".repeat(MIN_ACCOUNT_ID_LENGTH - 1);
+        let long = "a".repeat(MAX_ACCOUNT_ID_LENGTH + 1);
+        assert_eq!(validate_account_id(&short), Err(ValidationError::AccountIdLength { length: MIN_ACCOUNT_ID_LENGTH - 1 }));
+        assert_eq!(validate_account_id(&long), Err(ValidationError::AccountIdLength { length: MAX_ACCOUNT_ID_LENGTH + 1 }));
+    }
+
+    #[test]
+    fn rejects_invalid_account_id_characters() {
+        assert_eq!(validate_account_id("acc/0001"), Err(ValidationError::InvalidAccountIdCharacter { character: '/' }));
+        assert_eq!(validate_account_id("accöunt1"), Err(ValidationError::InvalidAccountIdCharacter { character: 'ö' }));
+    }
+
+    #[test]
+    fn reports_the_first_violation() {
+        assert_eq!(validate_new_account("Ann", "Lee", "account_1"), Ok(()));
+        assert_eq!(
+            validate_new_account("", "Lee2", "a"),
+            Err(ValidationError::EmptyName { field: "first_name" })
+        );
+        assert_eq!(
+            validate_new_account("Ann", "Lee2", "a"),
+            Err(ValidationError::InvalidNameCharacter { field: "last_name", character: '2' })
+        );
+        assert_eq!(validate_new_account("Ann", "Lee", "a"), Err(ValidationError::AccountIdLength { length: 1 }));
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+use crate::Address;
+
+// Addresses passed as arguments are base64url encoded strings of 32 bytes.
+// Parsing them returns an error describing what is wrong with the input,
+// instead of panicking in the middle of the transaction.
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AddressError {
+    /// The input is not valid base64url.
+    BadEncoding,
+    /// The input decodes to the given number of bytes instead of 32.
+    WrongLength(usize),
+}
+
+impl fmt::Display for AddressError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressError::BadEncoding =>
+                write!(f, "The address is not a valid base64url string."),
+            AddressError::WrongLength(length) =>
+                write!(f, "The address has {} bytes instead of 32.", length),
+        }
+    }
+}
+
+pub fn parse_address(address: &str) -> Result<Address, AddressError> {
+    let bytes = base64url::decode(address).map_err(|_| AddressError::BadEncoding)?;
+    let length = bytes.len();
+    bytes.try_into().map_err(|_| AddressError::WrongLength(length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_valid_address() {
+        assert_eq!(parse_address("-jUt6jrEfMRD1JM9n6_yAASl2cwsc4tg1Bqp07gvQpU").map(|address| address[0]), Ok(0xfa));
+        assert_eq!(parse_address(&base64url::encode([7u8; 32])), Ok([7u8; 32]));
+    }
+
+    #[test]
+    fn rejects_bad_encoding() {
+        assert_eq!(parse_address("not a base64url address!"), Err(AddressError::BadEncoding));
+        assert_eq!(parse_address("-jUt6jrEfMRD1JM9n6_yAASl2cwsc4tg1Bqp07gvQp+"), Err(AddressError::BadEncoding));
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        assert_eq!(parse_address(""), Err(AddressError::WrongLength(0)));
+        assert_eq!(parse_address(&base64url::encode([7u8; 31])), Err(AddressError::WrongLength(31)));
+        assert_eq!(parse_address(&base64url::encode([7u8; 33])), Err(AddressError::WrongLength(33)));
+    }
+}
@@ -0,0 +1,18 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Address;
+
+// Every call made by the proxy on behalf of a caller is recorded in world state, so that anyone
+// can review afterwards who made the proxy call what.
+
+/// Maximum number of records returned by one call of `audit_records`.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct AuditRecord {
+    pub caller: Address,
+    pub target: Address,
+    pub method: String,
+    pub value: u64,
+    pub block_number: u64,
+}
@@ -1,61 +1,718 @@
 use pchain_sdk::{
-    use_contract, call, contract, contract_methods
+    call, contract, contract_methods, storage, transaction, blockchain, crypto, collections::{FastMap, Vector}
 };
 
+mod address;
+mod audit;
+mod multicall;
+mod my_bank;
+mod my_collections;
+mod my_little_pony;
+mod reentrancy;
+mod spending;
+mod state;
+
+use audit::AuditRecord;
+use multicall::{Call, CallResult};
+use reentrancy::Lock;
+use spending::{Spending, SpendingError};
+
 // Contract Proxy serves as middle-man to another contract `MyLittlePony`.
 // This example shows how contract can interact with other contract by:
 // - calling entrypoint methods
 // - sending tokens from balance of this contract to other contract
+// - forwarding the tokens attached to a call, and refunding the remainder to the caller
+// - forwarding arbitrary calls to whitelisted contracts and methods, with an audit trail
+// - standing as a stable address in front of a replaceable implementation
+// - batching several calls in one transaction
+// - composing calls to other contracts `MyBank` and `MyCollections` in one entrypoint method
 
+type Address = [u8; 32];
+
+/// Key of the field `admin` in world state. It is absent until `init` is called.
+const ADMIN_KEY: [u8; 1] = [0];
+/// The zero address stands for no pending upgrade.
+const ZERO_ADDRESS: Address = [0u8; 32];
 
 /// ### Section 1:
-/// use macro `use_contract` to specify the contract action entrypoint methods in a trait.
-/// The address is hard-coded when using macro `use_contract`, ie. once you deployed this
-/// contract, the address cannot be changed.
-/// It is recommended to remove/comment out the methods that are not intended to be used.
-#[use_contract("-jUt6jrEfMRD1JM9n6_yAASl2cwsc4tg1Bqp07gvQpU")]
-pub trait MyLittlePony {
-    //fn self_introduction() -> String;
-    fn grow_up();
-    //fn change_person(name :String, age :u32, gender_name :String, description :String);
+/// The macro `use_contract` hard-codes the address of the other contract, ie. once you deployed this
+/// contract, the address cannot be changed. Instead, the address of `MyLittlePony` is stored in field
+/// `target`, set in `init` and upgradable by the admin (Section 6), and every proxied call reads it from world state.
+/// The addresses of `MyBank` and `MyCollections` are stored in fields `bank` and `collections` likewise (Section 8).
+#[contract]
+pub struct ContractProxy {
+    admin: Address,
+    target: Address,
+    whitelist: FastMap<Address, bool>,
+    implementations: Vector<Address>,
+    pending_implementation: Address,
+    upgrade_ready_at: u32,
+    upgrade_delay: u32,
+    spenders: FastMap<Address, bool>,
+    spending_period: u32,
+    period_cap: u64,
+    period_spending: u64,
+    period_spending_start: u32,
+    recipient_caps: FastMap<Address, u64>,
+    recipient_spending: FastMap<Address, Spending>,
+    allowed_methods: FastMap<(Address, String), bool>,
+    audit_log: Vector<AuditRecord>,
+    locked: bool,
+    forwarded_amounts: FastMap<Address, u64>,
+    bank: Address,
+    collections: Address,
 }
 
-#[contract]
-pub struct ContractProxy {}
+impl ContractProxy {
+    /// Logs the reason why the entrypoint method rejected the call.
+    fn reject(method: &str, reason: &str) {
+        pchain_sdk::log(
+            format!("proxy: {}", method).as_bytes(),
+            reason.as_bytes()
+        );
+    }
+
+    /// Returns the admin recorded by `init`, or None if the contract is not initialized yet.
+    fn admin() -> Option<Address> {
+        storage::get(&ADMIN_KEY).map(|_| Self::get_admin())
+    }
+
+    /// Checks that the calling account is the admin, and logs the reason of rejection otherwise.
+    fn ensure_admin(method: &str) -> bool {
+        let is_admin = Self::admin() == Some(transaction::calling_account());
+        if !is_admin {
+            Self::reject(method, "Only the admin can call this method.");
+        }
+        is_admin
+    }
+
+    /// Whether the account deployed this contract in the transaction with the nonce. The address of a contract
+    /// is the sha256 hash of the deploying account followed by the nonce of the deploy transaction (little-endian).
+    fn is_deployer(account: Address, deployment_nonce: u64) -> bool {
+        let mut pre_image = account.to_vec();
+        pre_image.extend(deployment_nonce.to_le_bytes());
+        crypto::sha256(pre_image) == transaction::current_account()
+    }
+
+    /// Makes the implementation current and appends it to the history.
+    fn activate(&mut self, implementation: Address) {
+        self.implementations.push(&implementation);
+        self.target = implementation;
+        self.pending_implementation = ZERO_ADDRESS;
+        pchain_sdk::log(
+            "proxy: upgrade".as_bytes(),
+            &implementation
+        );
+    }
+
+    /// A call can be relayed to any method of a whitelisted contract, or to an allowed method of another contract.
+    fn is_allowed(target: Address, method: &str) -> bool {
+        Self::get_whitelist().get(&target).unwrap_or(false)
+            || Self::get_allowed_methods().get(&(target, method.to_string())).unwrap_or(false)
+    }
+
+    /// Makes an outgoing call or transfer while holding the lock, so that the callee cannot call back into the proxy
+    /// until it returns. A reentrant call fails the whole transaction (see module `reentrancy`).
+    fn non_reentrant<T>(outgoing: impl FnOnce() -> T) -> T {
+        reentrancy::non_reentrant(&mut Lock(state::Storage), outgoing)
+    }
+
+    /// Settles the amount attached to the transaction once the outgoing calls are made: the part forwarded to the
+    /// callees is added to the account of the caller, and the remainder is refunded to the caller, so that the
+    /// proxy never keeps tokens attached to a proxied call. The map `forwarded_amounts` is read and written with
+    /// its getter and setter, so that the methods which only read the other fields do not need `self`.
+    fn settle(forwarded: u64) {
+        let caller = transaction::calling_account();
+        if forwarded > 0 {
+            let mut forwarded_amounts = Self::get_forwarded_amounts();
+            let total = forwarded_amounts.get(&caller).unwrap_or(0).saturating_add(forwarded);
+            forwarded_amounts.insert(&caller, total);
+            Self::set_forwarded_amounts(forwarded_amounts);
+            pchain_sdk::log(
+                "proxy: forwarded".as_bytes(),
+                &forwarded.to_le_bytes()
+            );
+        }
+        let remainder = transaction::amount().saturating_sub(forwarded);
+        if remainder > 0 {
+            Self::non_reentrant(|| pchain_sdk::transfer(caller, remainder));
+        }
+    }
+
+    /// Every call made on behalf of the caller goes through this check before it is made: the contract or method
+    /// must be allowed, and the value must be attached to this transaction, in addition to the value `forwarded` by
+    /// the calls already made in this transaction. The call is then appended to the audit log. The audit log is
+    /// read and written with its getter and setter, so the methods making calls have no receiver, which would
+    /// write back a stale copy of the log when the method returns.
+    fn authorize(target: Address, method: &str, value: u64, forwarded: u64) -> Result<(), &'static str> {
+        if !Self::is_allowed(target, method) {
+            return Err("The target contract or method is not allowed.");
+        }
+        if forwarded.saturating_add(value) > transaction::amount() {
+            return Err("The value exceeds the amount attached to the transaction.");
+        }
+        let mut audit_log = Self::get_audit_log();
+        audit_log.push(&AuditRecord {
+            caller: transaction::calling_account(),
+            target,
+            method: method.to_string(),
+            value,
+            block_number: blockchain::block_number(),
+        });
+        Self::set_audit_log(audit_log);
+        Ok(())
+    }
+
+    /// Same as `authorize`, for the calls whose rejection fails the transaction of the entrypoint method `method`.
+    fn ensure_authorized(method: &str, target: Address, callee_method: &str, value: u64) {
+        if let Err(rejection) = Self::authorize(target, callee_method, value, 0) {
+            panic!("proxy: {}: {}", method, rejection);
+        }
+    }
+
+    /// Relays a call with call_untyped() once it is authorized.
+    fn relay(target: Address, method: String, args: Vec<u8>, value: u64, forwarded: u64) -> Result<Option<Vec<u8>>, &'static str> {
+        Self::authorize(target, &method, value, forwarded)?;
+        Ok(Self::non_reentrant(|| pchain_sdk::call_untyped(target, &method, args, value)))
+    }
+
+    /// Checks the spending controls of `send_tokens` and records the spending, without transferring yet.
+    fn spend(&mut self, recipient: Address, value: u64) -> Result<(), SpendingError> {
+        let caller = transaction::calling_account();
+        if Self::admin() != Some(caller) && !self.spenders.get(&caller).unwrap_or(false) {
+            return Err(SpendingError::NotAllowed);
+        }
+        let now = blockchain::timestamp();
+        let period_spending = Spending { period_start: self.period_spending_start, spent: self.period_spending }
+            .spend(now, self.spending_period, value, self.period_cap)
+            .ok_or(SpendingError::PeriodCapExceeded { cap: self.period_cap })?;
+        let recipient_spending = match self.recipient_caps.get(&recipient) {
+            Some(cap) => Some(
+                self.recipient_spending.get(&recipient).unwrap_or_default()
+                    .spend(now, self.spending_period, value, cap)
+                    .ok_or(SpendingError::RecipientCapExceeded { cap })?
+            ),
+            None => None,
+        };
+        let balance = blockchain::balance();
+        if balance < value {
+            return Err(SpendingError::InsufficientBalance { balance });
+        }
+        self.period_spending_start = period_spending.period_start;
+        self.period_spending = period_spending.spent;
+        if let Some(recipient_spending) = recipient_spending {
+            self.recipient_spending.insert(&recipient, recipient_spending);
+        }
+        Ok(())
+    }
+
+    /// Parses a base64url encoded address passed as argument, and logs the reason of rejection otherwise.
+    fn parse_address(method: &str, address: &str) -> Option<Address> {
+        address::parse_address(address)
+            .map_err(|error| Self::reject(method, &error.to_string()))
+            .ok()
+    }
+}
 
 #[contract_methods]
 impl ContractProxy {
 
+    /// ### Section 1:
+    /// The calling account becomes the admin, and `target` is the base64url encoded address of `MyLittlePony`.
+    /// Upgrades of `target` take effect `upgrade_delay` seconds after they are requested (Section 6).
+    /// `bank` and `collections` are the base64url encoded addresses of `MyBank` and `MyCollections` (Section 8).
+    /// Only the account which deployed the contract can initialize it, so that nobody else can become the admin
+    /// by calling `init` first. It proves it with the nonce of its deploy transaction, see `is_deployer`.
+    /// A second call to `init` is refused.
+    #[call]
+    fn init(deployment_nonce: u64, target: String, upgrade_delay: u32, bank: String, collections: String) {
+        if Self::admin().is_some() {
+            Self::reject("init", "The proxy has already been initialized.");
+            return;
+        }
+        if !Self::is_deployer(transaction::calling_account(), deployment_nonce) {
+            Self::reject("init", "Only the deployer of the contract can call this method.");
+            return;
+        }
+        let addresses = Self::parse_address("init", &target)
+            .zip(Self::parse_address("init", &bank))
+            .zip(Self::parse_address("init", &collections));
+        if let Some(((target, bank), collections)) = addresses {
+            let mut proxy = ContractProxy {
+                admin: transaction::calling_account(),
+                target,
+                whitelist: FastMap::new(),
+                implementations: Vector::new(),
+                pending_implementation: ZERO_ADDRESS,
+                upgrade_ready_at: 0,
+                upgrade_delay,
+                spenders: FastMap::new(),
+                spending_period: spending::DEFAULT_SPENDING_PERIOD,
+                period_cap: 0,
+                period_spending: 0,
+                period_spending_start: 0,
+                recipient_caps: FastMap::new(),
+                recipient_spending: FastMap::new(),
+                allowed_methods: FastMap::new(),
+                audit_log: Vector::new(),
+                locked: false,
+                forwarded_amounts: FastMap::new(),
+                bank,
+                collections,
+            };
+            proxy.implementations.push(&target);
+            proxy.set();
+        }
+    }
+
     /// ### Section 2:
-    /// The trait will be transformed to mod by using macro `use_contract`. 
-    /// Calling the contract `MyLittlePony` can be simply calling associate methods according to defined method in the trait.
+    /// Calling the contract `MyLittlePony` can be simply calling functions of module `my_little_pony`,
+    /// which mirrors the module generated by macro `use_contract` but takes the address as argument.
     /// Value and Gas will be needed in cross contract call
+    /// As for `forward` (Section 5), the method of `MyLittlePony` must be allowed, `value` must be attached to this
+    /// transaction, and the call is recorded in the audit log, otherwise this call fails. `value` is forwarded to
+    /// the callee, and the remainder of the attached amount is refunded to the caller (see `settle`).
+    /// The return value of the callee is decoded and returned to the caller. If the callee fails or returns
+    /// no valid value, this call fails as well.
+    #[call]
+    fn self_introduction(locale: String, value: u64) -> String {
+        let target = Self::get_target();
+        Self::ensure_authorized("self_introduction", target, "self_introduction", value);
+        let introduction = Self::non_reentrant(|| my_little_pony::self_introduction(target, locale, value))
+            .unwrap_or_else(|error| panic!("proxy: self_introduction: {}", error));
+        Self::settle(value);
+        introduction
+    }
+
+    /// ### Section 2:
+    /// Returns the age of the pony.
+    #[call]
+    fn grow_up(value: u64) -> u32 {
+        let target = Self::get_target();
+        Self::ensure_authorized("grow_up", target, "grow_up", value);
+        let age = Self::non_reentrant(|| my_little_pony::grow_up(target, value))
+            .unwrap_or_else(|error| panic!("proxy: grow_up: {}", error));
+        Self::settle(value);
+        age
+    }
+
+    /// ### Section 2:
+    /// `MyLittlePony` sees this contract as the calling account, so the pony must be owned by this contract.
+    /// `MyLittlePony` does not fail when it rejects the change, e.g. because this contract is not the owner,
+    /// but logs the reason and returns false. The status is returned to the caller as is.
     #[call]
-    fn grow_up() {
-        my_little_pony::grow_up(0);
+    fn change_person(name: String, gender_name: String, description: Option<String>, value: u64) -> bool {
+        let target = Self::get_target();
+        Self::ensure_authorized("change_person", target, "change_person", value);
+        let changed = Self::non_reentrant(|| my_little_pony::change_person(target, name, gender_name, description, value))
+            .unwrap_or_else(|error| panic!("proxy: change_person: {}", error));
+        Self::settle(value);
+        changed
     }
 
     /// ### Section 3:
-    /// It is also possible to use call_untyped() instead of macro `use_contract` to make a cross contract call.
-    /// Address can also be passed as argument so that contract address is not necessary hard-coded.
+    /// It is also possible to use call_untyped() to make a cross contract call.
+    /// Address can also be read from world state so that contract address is not necessary hard-coded.
+    /// Every outgoing call of the proxy holds the reentrancy lock, see `non_reentrant`, and is authorized
+    /// as in Section 2.
     #[call]
-    fn grow_up_2() {
-        let contract_address = base64url::decode("-jUt6jrEfMRD1JM9n6_yAASl2cwsc4tg1Bqp07gvQpU").unwrap().try_into().unwrap();
-        pchain_sdk::call_untyped(
-            contract_address,
+    fn grow_up_2(value: u64) {
+        let target = Self::get_target();
+        Self::ensure_authorized("grow_up_2", target, "grow_up", value);
+        Self::non_reentrant(|| pchain_sdk::call_untyped(
+            target,
             "grow_up", 
             Vec::new(),
-            0);
+            value));
+        Self::settle(value);
     }
 
     /// ### Section 4:
     /// use method transfer() to send tokens from this contract balance to specific address.
+    /// `recipient` is the base64url encoded address of the account or contract receiving the tokens.
+    /// Only the admin and the allowed spenders can send tokens, within the caps of the current period.
+    /// The transfer is refused with a logged reason if the balance of the proxy is not enough.
     #[call]
-    fn send_tokens(value :u64){
-        let contract_address = base64url::decode("-jUt6jrEfMRD1JM9n6_yAASl2cwsc4tg1Bqp07gvQpU").unwrap().try_into().unwrap();
-        pchain_sdk::transfer(
-            contract_address,
+    fn send_tokens(&mut self, recipient: String, value :u64){
+        let recipient = match Self::parse_address("send_tokens", &recipient) {
+            Some(recipient) => recipient,
+            None => return,
+        };
+        if let Err(error) = self.spend(recipient, value) {
+            return Self::reject("send_tokens", &error.to_string());
+        }
+        Self::non_reentrant(|| pchain_sdk::transfer(
+            recipient,
             value
+        ));
+    }
+
+    /// ### Section 4:
+    /// The admin allows or disallows an account to call `send_tokens`.
+    #[call]
+    fn set_spender(&mut self, spender: String, allowed: bool) {
+        if !Self::ensure_admin("set_spender") {
+            return;
+        }
+        if let Some(spender) = Self::parse_address("set_spender", &spender) {
+            if allowed {
+                self.spenders.insert(&spender, true);
+            } else {
+                self.spenders.remove(&spender);
+            }
+        }
+    }
+
+    /// ### Section 4:
+    /// The admin sets the length of the period in seconds and the total amount which can be sent in a period.
+    /// The cap is 0 after `init`, so no tokens can be sent until the admin sets it. A period of 0 never ends.
+    #[call]
+    fn set_spending_cap(spending_period: u32, period_cap: u64) {
+        if !Self::ensure_admin("set_spending_cap") {
+            return;
+        }
+        Self::set_spending_period(spending_period);
+        Self::set_period_cap(period_cap);
+    }
+
+    /// ### Section 4:
+    /// The admin caps the amount which can be sent to a recipient in a period, or removes the cap with None.
+    /// Recipients without a cap are only limited by the total cap of the period.
+    #[call]
+    fn set_recipient_cap(&mut self, recipient: String, cap: Option<u64>) {
+        if !Self::ensure_admin("set_recipient_cap") {
+            return;
+        }
+        if let Some(recipient) = Self::parse_address("set_recipient_cap", &recipient) {
+            match cap {
+                Some(cap) => self.recipient_caps.insert(&recipient, cap),
+                None => self.recipient_caps.remove(&recipient),
+            }
+        }
+    }
+
+    /// ### Section 5:
+    /// The admin manages a whitelist of contracts that `forward` may call.
+    #[call]
+    fn add_to_whitelist(&mut self, target: String) {
+        if !Self::ensure_admin("add_to_whitelist") {
+            return;
+        }
+        if let Some(target) = Self::parse_address("add_to_whitelist", &target) {
+            self.whitelist.insert(&target, true);
+        }
+    }
+
+    /// ### Section 5:
+    #[call]
+    fn remove_from_whitelist(&mut self, target: String) {
+        if !Self::ensure_admin("remove_from_whitelist") {
+            return;
+        }
+        if let Some(target) = Self::parse_address("remove_from_whitelist", &target) {
+            self.whitelist.remove(&target);
+        }
+    }
+
+    /// ### Section 5:
+    #[call]
+    fn is_whitelisted(&self, target: String) -> bool {
+        address::parse_address(&target)
+            .is_ok_and(|target| self.whitelist.get(&target).unwrap_or(false))
+    }
+
+    /// ### Section 5:
+    /// Besides whole contracts, the admin can allow single methods of a contract to be called by `forward`.
+    #[call]
+    fn allow_method(&mut self, target: String, method: String) {
+        if !Self::ensure_admin("allow_method") {
+            return;
+        }
+        if let Some(target) = Self::parse_address("allow_method", &target) {
+            self.allowed_methods.insert(&(target, method), true);
+        }
+    }
+
+    /// ### Section 5:
+    #[call]
+    fn disallow_method(&mut self, target: String, method: String) {
+        if !Self::ensure_admin("disallow_method") {
+            return;
+        }
+        if let Some(target) = Self::parse_address("disallow_method", &target) {
+            self.allowed_methods.remove(&(target, method));
+        }
+    }
+
+    /// ### Section 5:
+    /// True if `forward` can call the method, either because it is allowed or because its contract is whitelisted.
+    #[call]
+    fn is_method_allowed(&self, target: String, method: String) -> bool {
+        address::parse_address(&target)
+            .is_ok_and(|target| Self::is_allowed(target, &method))
+    }
+
+    /// ### Section 5:
+    /// call_untyped() can call any method of any contract. `forward` relays a call with arguments already
+    /// Borsh-encoded by the caller to an allowed contract or method, and returns the raw return value of the callee.
+    /// The value must be attached to this transaction, so that the balance of the proxy is not spent on behalf of the caller.
+    /// Every relayed call is recorded in the audit log. The part of the attached amount which is not forwarded
+    /// is refunded to the caller, as well as the whole amount if the call is rejected.
+    #[call]
+    fn forward(target: String, method: String, args: Vec<u8>, value: u64) -> Option<Vec<u8>> {
+        let relayed = address::parse_address(&target)
+            .map_err(|error| error.to_string())
+            .and_then(|target| Self::relay(target, method, args, value, 0).map_err(str::to_string));
+        match relayed {
+            Ok(return_value) => {
+                Self::settle(value);
+                return_value
+            },
+            Err(rejection) => {
+                Self::reject("forward", &rejection);
+                Self::settle(0);
+                None
+            }
+        }
+    }
+
+    /// ### Section 5:
+    /// The total value that the proxied calls of an account have forwarded to callees.
+    #[call]
+    fn forwarded_amount(&self, caller: String) -> u64 {
+        address::parse_address(&caller)
+            .map_or(0, |caller| self.forwarded_amounts.get(&caller).unwrap_or(0))
+    }
+
+    /// ### Section 5:
+    /// Returns at most `limit` records of the audit log (and never more than `MAX_PAGE_SIZE`), from the oldest,
+    /// starting at index `start`. Use `start` plus the number of returned records as `start` of the next page.
+    /// A `start` beyond the indexes of the log returns no records.
+    #[call]
+    fn audit_records(&self, start: u64, limit: u32) -> Vec<AuditRecord> {
+        let start = match usize::try_from(start) {
+            Ok(start) => start,
+            Err(_) => return Vec::new(),
+        };
+        let end = self.audit_log.len().min(start.saturating_add(limit.min(audit::MAX_PAGE_SIZE) as usize));
+        (start..end)
+            .filter_map(|index| self.audit_log.get(index).cloned())
+            .collect()
+    }
+
+    /// ### Section 5:
+    #[call]
+    fn audit_log_length(&self) -> u64 {
+        self.audit_log.len() as u64
+    }
+
+    /// ### Section 6:
+    /// The proxy is a stable address in front of a replaceable implementation, the contract at `target`.
+    /// The admin requests an upgrade to a new implementation, which takes effect after `upgrade_delay`
+    /// seconds with `apply_upgrade`, or immediately if there is no delay. The delay gives the users of the
+    /// proxy time to notice an upgrade before it happens.
+    #[call]
+    fn upgrade(&mut self, implementation: String) {
+        if !Self::ensure_admin("upgrade") {
+            return;
+        }
+        let implementation = match Self::parse_address("upgrade", &implementation) {
+            Some(implementation) => implementation,
+            None => return,
+        };
+        if self.upgrade_delay == 0 {
+            self.activate(implementation);
+        } else {
+            self.pending_implementation = implementation;
+            self.upgrade_ready_at = blockchain::timestamp().saturating_add(self.upgrade_delay);
+            pchain_sdk::log(
+                "proxy: upgrade_requested".as_bytes(),
+                &implementation
+            );
+        }
+    }
+
+    /// ### Section 6:
+    /// Activates the pending upgrade once its delay has passed.
+    #[call]
+    fn apply_upgrade(&mut self) {
+        if !Self::ensure_admin("apply_upgrade") {
+            return;
+        }
+        if self.pending_implementation == ZERO_ADDRESS {
+            return Self::reject("apply_upgrade", "There is no pending upgrade.");
+        }
+        if blockchain::timestamp() < self.upgrade_ready_at {
+            return Self::reject("apply_upgrade", &format!("The upgrade cannot be applied before timestamp {}.", self.upgrade_ready_at));
+        }
+        self.activate(self.pending_implementation);
+    }
+
+    /// ### Section 6:
+    #[call]
+    fn cancel_upgrade() {
+        if !Self::ensure_admin("cancel_upgrade") {
+            return;
+        }
+        Self::set_pending_implementation(ZERO_ADDRESS);
+    }
+
+    /// ### Section 6:
+    /// Returns to the previous implementation in the history, immediately, and cancels any pending upgrade.
+    #[call]
+    fn rollback(&mut self) {
+        if !Self::ensure_admin("rollback") {
+            return;
+        }
+        let versions = self.implementations.len();
+        if versions < 2 {
+            return Self::reject("rollback", "There is no previous implementation.");
+        }
+        self.implementations.pop();
+        self.target = self.implementations[versions - 2];
+        self.pending_implementation = ZERO_ADDRESS;
+        pchain_sdk::log(
+            "proxy: rollback".as_bytes(),
+            &self.target
         );
     }
-}
\ No newline at end of file
+
+    /// ### Section 6:
+    /// The admin changes the delay of the upgrades requested afterwards.
+    #[call]
+    fn set_delay(upgrade_delay: u32) {
+        if !Self::ensure_admin("set_delay") {
+            return;
+        }
+        Self::set_upgrade_delay(upgrade_delay);
+    }
+
+    /// ### Section 6:
+    /// The version is the number of implementations activated so far, starting from 1 with the one set in `init`.
+    #[call]
+    fn version(&self) -> u64 {
+        self.implementations.len() as u64
+    }
+
+    /// ### Section 6:
+    /// The implementations from the first to the current one.
+    #[call]
+    fn implementation_history(&self) -> Vec<Address> {
+        self.implementations.iter().copied().collect()
+    }
+
+    /// ### Section 6:
+    /// The pending implementation and the timestamp from which it can be applied.
+    #[call]
+    fn pending_upgrade(&self) -> Option<(Address, u32)> {
+        Some((self.pending_implementation, self.upgrade_ready_at))
+            .filter(|(implementation, _)| *implementation != ZERO_ADDRESS)
+    }
+
+    /// ### Section 6:
+    /// Forwards a call with Borsh-encoded arguments to the current implementation, and returns its raw return value.
+    /// As with `forward`, the method must be allowed (Section 5), the value must be attached to this transaction,
+    /// the remainder is refunded, and the call is recorded in the audit log. The implementation sees this contract
+    /// as the calling account, so without the allow-list anyone could act as the proxy, e.g. to transfer its pony.
+    #[call]
+    fn call_implementation(method: String, args: Vec<u8>, value: u64) -> Option<Vec<u8>> {
+        match Self::relay(Self::get_target(), method, args, value, 0) {
+            Ok(return_value) => {
+                Self::settle(value);
+                return_value
+            },
+            Err(rejection) => {
+                Self::reject("call_implementation", rejection);
+                Self::settle(0);
+                None
+            }
+        }
+    }
+
+    /// ### Section 7:
+    /// Executes the calls in order with call_untyped(), and returns the results in the same order.
+    /// Every call must target an allowed contract or method as with `forward`, and the values of all calls must be attached
+    /// to this transaction. With `skip_disallowed`, a call which the proxy refuses to make, e.g. because its method is
+    /// not allowed, is skipped with its reason, and the other calls are still made. Otherwise, such a call fails the
+    /// whole transaction, so that the calls already made are reverted as well.
+    /// A call failing in the callee always fails the whole transaction, as call_untyped() does not return to the proxy
+    /// when the callee fails, so failures of the callees cannot be isolated.
+    /// Every call made is recorded in the audit log, and the part of the attached amount not forwarded is refunded.
+    #[call]
+    fn multicall(calls: Vec<Call>, skip_disallowed: bool) -> Vec<CallResult> {
+        let mut forwarded: u64 = 0;
+        let mut results = Vec::with_capacity(calls.len());
+        for (index, call) in calls.into_iter().enumerate() {
+            let value = call.value;
+            let relayed = address::parse_address(&call.target)
+                .map_err(|error| error.to_string())
+                .and_then(|target| Self::relay(target, call.method, call.args, value, forwarded).map_err(str::to_string));
+            match relayed {
+                Ok(return_value) => {
+                    forwarded += value;
+                    results.push(CallResult::Returned(return_value));
+                },
+                Err(reason) if !skip_disallowed => panic!("proxy: multicall: call {}: {}", index, reason),
+                Err(reason) => {
+                    Self::reject("multicall", &format!("call {}: {}", index, reason));
+                    results.push(CallResult::Skipped(reason));
+                }
+            }
+        }
+        Self::settle(forwarded);
+        results
+    }
+
+    /// ### Section 8:
+    /// The contracts `MyBank` and `MyCollections` are called one after the other in a single transaction, at the
+    /// addresses stored in fields `bank` and `collections`. This opens a bank account and registers `name` for the
+    /// calling account in the address resolver of `MyCollections`, and returns the id of the opened account.
+    /// Both calls are authorized as in Section 2, so the admin must allow the methods `open_account` of `MyBank`
+    /// and `add_record` of `MyCollections` (Section 5).
+    /// If `MyBank` rejects the account details, the rejection is logged, the name is not registered, and None is
+    /// returned. If either call fails, the whole transaction fails.
+    #[call]
+    fn open_account_with_name(first_name: String, last_name: String, account_id: String, initial_deposit: u64, name: String) -> Option<String> {
+        let caller = transaction::calling_account();
+        let (bank, collections) = (Self::get_bank(), Self::get_collections());
+        Self::ensure_authorized("open_account_with_name", bank, "open_account", 0);
+        let opened = Self::non_reentrant(|| my_bank::open_account(bank, first_name, last_name, account_id, initial_deposit, 0));
+        let account_id = match opened {
+            Ok(account_id) => {
+                Self::ensure_authorized("open_account_with_name", collections, "add_record", 0);
+                Self::non_reentrant(|| my_collections::add_record(collections, caller, name, 0));
+                Some(account_id)
+            },
+            Err(rejection) => {
+                Self::reject("open_account_with_name", &rejection);
+                None
+            }
+        };
+        Self::settle(0);
+        account_id
+    }
+
+    /// ### Section 8:
+    /// The admin changes the base64url encoded address of `MyBank`.
+    #[call]
+    fn set_bank_address(bank: String) {
+        if !Self::ensure_admin("set_bank_address") {
+            return;
+        }
+        if let Some(bank) = Self::parse_address("set_bank_address", &bank) {
+            Self::set_bank(bank);
+        }
+    }
+
+    /// ### Section 8:
+    /// The admin changes the base64url encoded address of `MyCollections`.
+    #[call]
+    fn set_collections_address(collections: String) {
+        if !Self::ensure_admin("set_collections_address") {
+            return;
+        }
+        if let Some(collections) = Self::parse_address("set_collections_address", &collections) {
+            Self::set_collections(collections);
+        }
+    }
+}
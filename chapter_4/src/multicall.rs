@@ -0,0 +1,23 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+// Types of the entrypoint method `multicall`, which executes several calls in
+// one transaction. Both are Borsh-encoded: a list of `Call` as argument, and a
+// list of `CallResult` in the same order as return value.
+
+/// A call to the method of a contract, with arguments already Borsh-encoded.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Call {
+    /// Base64url encoded address of the contract.
+    pub target: String,
+    pub method: String,
+    pub args: Vec<u8>,
+    pub value: u64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub enum CallResult {
+    /// The call was made, with the raw return value of the callee if any.
+    Returned(Option<Vec<u8>>),
+    /// The call was not made, for the given reason.
+    Skipped(String),
+}
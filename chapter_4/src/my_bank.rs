@@ -0,0 +1,25 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Address;
+
+// Bindings of the entrypoint methods of the contract MyBank (chapter_3), which
+// take the address as an argument as in module `my_little_pony`. Only the
+// methods used by ContractProxy are bound.
+
+/// Returns the id of the opened account, or the reason why MyBank rejected the account details.
+/// Fails the transaction if MyBank returns no valid value.
+pub fn open_account(contract_address: Address, first_name: String, last_name: String, account_id: String, initial_deposit: u64, value: u64) -> Result<String, String> {
+    let return_value = pchain_sdk::call_untyped(
+        contract_address,
+        "open_account",
+        vec![
+            first_name.try_to_vec().unwrap(),
+            last_name.try_to_vec().unwrap(),
+            account_id.try_to_vec().unwrap(),
+            initial_deposit.try_to_vec().unwrap(),
+        ].try_to_vec().unwrap(),
+        value)
+        .expect("proxy: MyBank returned no value.");
+    Result::<String, String>::deserialize(&mut return_value.as_slice())
+        .expect("proxy: MyBank returned a value of unexpected type.")
+}
@@ -0,0 +1,18 @@
+use borsh::BorshSerialize;
+
+use crate::Address;
+
+// Bindings of the entrypoint methods of the contract MyCollections (chapter_5),
+// which take the address as an argument as in module `my_little_pony`. Only the
+// methods used by ContractProxy are bound.
+
+pub fn add_record(contract_address: Address, address: Address, name: String, value: u64) {
+    pchain_sdk::call_untyped(
+        contract_address,
+        "add_record",
+        vec![
+            address.try_to_vec().unwrap(),
+            name.try_to_vec().unwrap(),
+        ].try_to_vec().unwrap(),
+        value);
+}
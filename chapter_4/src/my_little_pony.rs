@@ -0,0 +1,69 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Address;
+
+// Bindings of the entrypoint methods of the contract MyLittlePony (chapter_2).
+//
+// The module generated by macro `use_contract` calls a contract at an address
+// hard-coded in the macro. These functions make the same cross contract calls,
+// but take the address as an argument, so that ContractProxy can call the
+// address stored in its world state. Arguments are Borsh-encoded one by one,
+// and the return value of the callee is decoded into the declared type.
+
+#[derive(Debug)]
+pub enum CallError {
+    /// The callee returned nothing, while a return value was expected.
+    NoReturnValue,
+    /// The return value of the callee cannot be decoded into the expected type.
+    InvalidReturnValue,
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::NoReturnValue => write!(f, "MyLittlePony returned no value."),
+            CallError::InvalidReturnValue => write!(f, "MyLittlePony returned a value of unexpected type."),
+        }
+    }
+}
+
+/// Encodes the list of Borsh-encoded arguments of a cross contract call.
+fn arguments(args: Vec<Vec<u8>>) -> Vec<u8> {
+    args.try_to_vec().unwrap()
+}
+
+fn return_value<T: BorshDeserialize>(return_value: Option<Vec<u8>>) -> Result<T, CallError> {
+    let return_value = return_value.ok_or(CallError::NoReturnValue)?;
+    T::deserialize(&mut return_value.as_slice()).map_err(|_| CallError::InvalidReturnValue)
+}
+
+pub fn self_introduction(contract_address: Address, locale: String, value: u64) -> Result<String, CallError> {
+    return_value(pchain_sdk::call_untyped(
+        contract_address,
+        "self_introduction",
+        arguments(vec![locale.try_to_vec().unwrap()]),
+        value))
+}
+
+pub fn grow_up(contract_address: Address, value: u64) -> Result<u32, CallError> {
+    return_value(pchain_sdk::call_untyped(
+        contract_address,
+        "grow_up",
+        Vec::new(),
+        value))
+}
+
+/// Returns whether the pony is changed. MyLittlePony rejects a change by logging the reason and returning false.
+pub fn change_person(contract_address: Address, name: String, gender_name: String, description: Option<String>, value: u64) -> Result<bool, CallError> {
+    return_value(pchain_sdk::call_untyped(
+        contract_address,
+        "change_person",
+        arguments(vec![
+            name.try_to_vec().unwrap(),
+            gender_name.try_to_vec().unwrap(),
+            description.try_to_vec().unwrap(),
+        ]),
+        value))
+}
@@ -0,0 +1,115 @@
+use crate::state::{self, WorldState};
+
+// The proxy calls contracts it does not control, which can call back into the
+// proxy before the outgoing call returns. Every outgoing call or transfer holds
+// a lock, and a call made while the lock is held fails the whole transaction.
+// The lock is kept in world state, so that it is seen by the reentrant call.
+
+/// Key of the field `locked` of `ContractProxy` in world state.
+pub const LOCKED_KEY: [u8; 1] = [16];
+
+/// The lock kept in field `locked`. It is read and written at its key directly, rather than through
+/// `self`, which is only saved when the entrypoint method returns.
+pub struct Lock<S: WorldState>(pub S);
+
+impl<S: WorldState> Lock<S> {
+    /// An absent field, before `init`, stands for a released lock.
+    pub fn is_locked(&self) -> bool {
+        state::read(&self.0, &LOCKED_KEY).unwrap_or(false)
+    }
+
+    pub fn set_locked(&mut self, locked: bool) {
+        state::write(&mut self.0, &LOCKED_KEY, &locked);
+    }
+}
+
+/// Makes the outgoing call while holding the lock. Panics if the lock is already held.
+pub fn non_reentrant<S: WorldState, T>(lock: &mut Lock<S>, outgoing: impl FnOnce() -> T) -> T {
+    if lock.is_locked() {
+        panic!("proxy: Reentrant calls are not allowed.");
+    }
+    lock.set_locked(true);
+    let result = outgoing();
+    lock.set_locked(false);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::memory::MemoryState;
+
+    /// An entrypoint method of the proxy which makes one outgoing call to `callee`.
+    /// With a receiver `&mut self`, the field `locked` is loaded with the other fields before the method,
+    /// and written back after it.
+    fn entrypoint(state: &MemoryState, receiver: bool, callee: impl FnOnce()) {
+        let loaded = if receiver { state::read::<bool>(state, &LOCKED_KEY) } else { None };
+        non_reentrant(&mut Lock(state.clone()), callee);
+        if let Some(locked) = loaded {
+            state::write(&mut state.clone(), &LOCKED_KEY, &locked);
+        }
+    }
+
+    /// An entrypoint method of the proxy with receiver `&mut self` which makes no outgoing call, e.g. `set_spender`.
+    fn entrypoint_without_call(state: &MemoryState) {
+        let loaded = state::read::<bool>(state, &LOCKED_KEY).unwrap();
+        state::write(&mut state.clone(), &LOCKED_KEY, &loaded);
+    }
+
+    /// The world state after `init`.
+    fn initialized() -> MemoryState {
+        let mut state = MemoryState::default();
+        state::write(&mut state, &LOCKED_KEY, &false);
+        state
+    }
+
+    #[test]
+    #[should_panic(expected = "Reentrant calls are not allowed.")]
+    fn rejects_reentrant_callee() {
+        let state = initialized();
+        // The callee calls back into the proxy before returning.
+        entrypoint(&state, false, || entrypoint(&state, false, || ()));
+    }
+
+    #[test]
+    #[should_panic(expected = "Reentrant calls are not allowed.")]
+    fn rejects_reentrant_callee_of_method_with_receiver() {
+        let state = initialized();
+        entrypoint(&state, true, || entrypoint(&state, true, || ()));
+    }
+
+    #[test]
+    fn releases_lock_after_call() {
+        let state = initialized();
+        entrypoint(&state, false, || assert!(Lock(state.clone()).is_locked()));
+        assert!(!Lock(state.clone()).is_locked());
+    }
+
+    #[test]
+    fn write_back_of_receiver_keeps_lock_released() {
+        let state = initialized();
+        entrypoint(&state, true, || ());
+        assert!(!Lock(state.clone()).is_locked());
+    }
+
+    #[test]
+    fn callee_calling_method_without_outgoing_call_keeps_lock() {
+        let state = initialized();
+        entrypoint(&state, true, || {
+            entrypoint_without_call(&state);
+            // The write-back of the reentrant method stores the lock as it loaded it, ie. held.
+            assert!(Lock(state.clone()).is_locked());
+        });
+        assert!(!Lock(state.clone()).is_locked());
+    }
+
+    #[test]
+    fn allows_sequential_calls() {
+        let state = initialized();
+        let mut calls = 0;
+        for receiver in [false, true, false] {
+            entrypoint(&state, receiver, || calls += 1);
+        }
+        assert_eq!(calls, 3);
+    }
+}
@@ -0,0 +1,60 @@
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+// Spending controls of `send_tokens`. Spending is capped per period of time,
+// both in total and per recipient. Periods are fixed windows of block
+// timestamps, so the amount spent is reset when a new window starts.
+
+/// Length of the spending period in seconds set by `init`.
+pub const DEFAULT_SPENDING_PERIOD: u32 = 86_400;
+
+/// Amount spent in the period starting at `period_start`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Default)]
+pub struct Spending {
+    pub period_start: u32,
+    pub spent: u64,
+}
+
+impl Spending {
+    /// Returns the spending after `value` is spent at timestamp `now`, or None if it would exceed `cap`.
+    pub fn spend(self, now: u32, period: u32, value: u64, cap: u64) -> Option<Spending> {
+        let period_start = start_of_period(now, period);
+        let spent = if self.period_start == period_start { self.spent } else { 0 };
+        spent.checked_add(value)
+            .filter(|spent| *spent <= cap)
+            .map(|spent| Spending { period_start, spent })
+    }
+}
+
+/// Start of the period which contains the timestamp `now`.
+fn start_of_period(now: u32, period: u32) -> u32 {
+    if period == 0 { 0 } else { now - now % period }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SpendingError {
+    /// The calling account is neither the admin nor an allowed spender.
+    NotAllowed,
+    /// The value exceeds what is left of the cap of the period.
+    PeriodCapExceeded { cap: u64 },
+    /// The value exceeds what is left of the cap of the recipient for the period.
+    RecipientCapExceeded { cap: u64 },
+    /// The balance of the proxy is lower than the value.
+    InsufficientBalance { balance: u64 },
+}
+
+impl fmt::Display for SpendingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpendingError::NotAllowed =>
+                write!(f, "The calling account is not allowed to send tokens."),
+            SpendingError::PeriodCapExceeded { cap } =>
+                write!(f, "The value exceeds the cap of {} per period.", cap),
+            SpendingError::RecipientCapExceeded { cap } =>
+                write!(f, "The value exceeds the cap of {} per period for the recipient.", cap),
+            SpendingError::InsufficientBalance { balance } =>
+                write!(f, "The balance of the proxy is only {}.", balance),
+        }
+    }
+}
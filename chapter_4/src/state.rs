@@ -0,0 +1,55 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use pchain_sdk::storage;
+
+// Raw key-value pairs of the world state. The fields of the contract struct are
+// read and written with their getters and setters, while the code working with
+// keys directly (e.g. the reentrancy lock) goes through `WorldState`, so that it
+// also runs against the in-memory state of the tests.
+
+pub trait WorldState {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>>;
+    fn set(&mut self, key: &[u8], value: &[u8]);
+}
+
+/// The world state of the contract, accessed with the SDK.
+pub struct Storage;
+
+impl WorldState for Storage {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        storage::get(key)
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        storage::set(key, value)
+    }
+}
+
+pub fn read<T: BorshDeserialize>(state: &impl WorldState, key: &[u8]) -> Option<T> {
+    state.get(key).and_then(|bytes| T::deserialize(&mut bytes.as_ref()).ok())
+}
+
+pub fn write<T: BorshSerialize>(state: &mut impl WorldState, key: &[u8], value: &T) {
+    state.set(key, &value.try_to_vec().unwrap());
+}
+
+#[cfg(test)]
+pub mod memory {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+    use super::WorldState;
+
+    /// World state kept in memory. Its clones share the same key-value pairs, as the
+    /// contract and the contracts it calls see the same world state.
+    #[derive(Clone, Default)]
+    pub struct MemoryState(Rc<RefCell<HashMap<Vec<u8>, Vec<u8>>>>);
+
+    impl WorldState for MemoryState {
+        fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+            self.0.borrow().get(key).cloned()
+        }
+
+        fn set(&mut self, key: &[u8], value: &[u8]) {
+            self.0.borrow_mut().insert(key.to_vec(), value.to_vec());
+        }
+    }
+}
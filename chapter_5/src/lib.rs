@@ -44,9 +44,19 @@ impl MyCollections {
     /// Here we use the receiver `&self` without loading data before executing this method. 
     #[call]
     fn pick(&self, index: usize) -> Option<i32> {
-        // Actual loading happens here. 
-        // Dereference as immutable and call functions from iterator
-        self.pretty_numbers.iter().nth(index).map(|value| *value)
+        // Actual loading happens here, only for the element at the index.
+        // Dereference as immutable and get the element by index
+        self.pretty_numbers.get(index).copied()
+    }
+
+    /// ### Section 2 - Vector: 
+    /// Here we use the receiver `&mut self` without loading data before executing this method. 
+    /// Replaces the element at the index and returns the previous one, or None if the index is out of bounds.
+    #[call]
+    fn replace(&mut self, index: usize, num: i32) -> Option<i32> {
+        // Only the element at the index is loaded, and then saved after this method
+        self.pretty_numbers.get_mut(index)
+            .map(|value| std::mem::replace(value, num))
     }
 
     /// ### Section 2 - Vector: 
@@ -59,6 +69,35 @@ impl MyCollections {
         // Actual saving happens after this method
     }
 
+    /// ### Section 2 - Vector: 
+    /// Removes the last element and returns it, or None if the vector is empty.
+    #[call]
+    fn pop(&mut self) -> Option<i32> {
+        // Read the last element before it is removed
+        let last = self.pretty_numbers.len().checked_sub(1)
+            .and_then(|index| self.pretty_numbers.get(index).copied());
+        self.pretty_numbers.pop();
+        last
+        // Actual saving happens after this method
+    }
+
+    /// ### Section 2 - Vector: 
+    /// Removes the elements from the end until at most `len` are left, and returns the new length.
+    #[call]
+    fn truncate(&mut self, len: usize) -> usize {
+        while self.pretty_numbers.len() > len {
+            self.pretty_numbers.pop();
+        }
+        self.pretty_numbers.len()
+    }
+
+    /// ### Section 2 - Vector: 
+    /// Removes all the elements. Each element is removed from world state one by one, so the gas cost grows with the length.
+    #[call]
+    fn clear_numbers(&mut self) {
+        self.truncate(0);
+    }
+
     /// ### Section 3 - FastMap: 
     /// Here we use the receiver `&self` without loading data before executing this method. 
     #[call]
@@ -77,6 +116,14 @@ impl MyCollections {
         // Actual saving happens after this method
     }
 
+    /// ### Section 3 - FastMap: 
+    /// Removes the record of the address from world state.
+    /// Note that a FastMap cannot be cleared, because it does not keep track of its keys.
+    #[call]
+    fn remove_record(&mut self, address: Address) {
+        self.address_resolver.remove(&address);
+    }
+
     /// ### Section 4 - IterableMap: 
     /// Here we use the receiver `&self` without loading data before executing this method. 
     #[call]
@@ -95,6 +142,20 @@ impl MyCollections {
         // Actual saving happens after this method
     }
 
+    /// ### Section 4 - IterableMap: 
+    /// Removes the item, so that it is no longer counted by `total_price`.
+    #[call]
+    fn remove_price(&mut self, item: String) {
+        self.prices.remove(&item);
+    }
+
+    /// ### Section 4 - IterableMap: 
+    /// Unlike FastMap, IterableMap keeps track of its keys, so all the items can be removed at once.
+    #[call]
+    fn clear_prices(&mut self) {
+        self.prices.clear();
+    }
+
     /// ### Section 4 - IterableMap: 
     /// This methods the iterablemap to calculate sum of the values by iterating each item.
     #[call]